@@ -1,24 +1,23 @@
 extern crate cpp_build;
 
-use std::convert::TryInto;
 use std::{env, fs, path, process};
 
 fn main() {
     //
     let src_dir = std::env::current_dir().unwrap();
     //
-    let mut out_dir: path::PathBuf = env::var("OUT_DIR").unwrap().try_into().unwrap();
+    let mut out_dir: path::PathBuf = env::var("OUT_DIR").unwrap().into();
     out_dir.push("voro++");
     let _ = fs::create_dir(&out_dir);
     //
     process::Command::new("cmake")
         .current_dir(&out_dir)
-        .args(&[src_dir.as_path().to_str().unwrap()])
+        .args([src_dir.as_path().to_str().unwrap()])
         .status()
         .unwrap();
     process::Command::new("make")
         .current_dir(&out_dir)
-        .args(&["-j"])
+        .args(["-j"])
         .status()
         .unwrap();
     //
@@ -29,4 +28,8 @@ fn main() {
     );
     //
     cpp_build::Config::new().include("src").build("src/lib.rs");
+    // The C++ compiler emits "rerun-if-env-changed" lines, which turn off
+    // cargo's default of rerunning this script when any file changes. The
+    // cpp! macros in the Rust sources must be extracted again when they change.
+    println!("cargo:rerun-if-changed=src");
 }
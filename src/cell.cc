@@ -80,6 +80,15 @@ void voronoicell_base::copy(voronoicell_base* vb) {
 	for(i=0;i<(p<<2);i++) pts[i]=vb->pts[i];
 }
 
+/** Copies the information from a voronoicell_neighbor class into this class,
+ * discarding the neighbor information, and extending memory allocation if
+ * necessary.
+ * \param[in] c the class to copy. */
+void voronoicell::operator=(voronoicell_neighbor &c) {
+	voronoicell_base *vb=((voronoicell_base*) &c);
+	check_memory_for_copy(*this,vb);copy(vb);
+}
+
 /** Copies the information from another voronoicell class into this
  * class, extending memory allocation if necessary.
  * \param[in] c the class to copy. */
@@ -403,24 +412,99 @@ void voronoicell_base::init_tetrahedron_base(double x0,double y0,double z0,doubl
 	*nu=nu[1]=nu[2]=nu[3]=3;
 }
 
+/** Initializes the cell to be an arbitrary convex polyhedron, given the
+ * neighbors of each vertex. The neighbors of each vertex must be listed in the
+ * same rotational order as the other routines use, so that the face that is
+ * clockwise from the edge to neighbor j is also anticlockwise from the edge to
+ * neighbor j+1.
+ * \param[in] vc a reference to the specialized version of the calling class.
+ * \param[in] p_ the number of vertices.
+ * \param[in] v the coordinates of the vertices.
+ * \param[in] nu_ the order of each vertex.
+ * \param[in] ed_ the neighbors of each vertex, listed consecutively. */
+template<class vc_class>
+void voronoicell_base::init_polyhedron_base(vc_class &vc,int p_,const double *v,const int *nu_,const int *ed_) {
+	int i,j,n,*q;
+	for(i=0;i<current_vertex_order;i++) mec[i]=0;
+	for(i=0;i<p_;i++) {
+		n=nu_[i];
+		if(n<3) voro_fatal_error("Vertex order is less than three",VOROPP_INTERNAL_ERROR);
+		while(current_vertex_order<=n) add_memory_vorder(vc);
+	}
+
+	// Count the vertices of each order separately, since mec must stay zero
+	// while the memory is allocated, so that add_memory does not try to
+	// relocate any vertices
+	std::vector<int> count(current_vertex_order,0);
+	for(i=0;i<p_;i++) count[nu_[i]]++;
+	for(i=0;i<current_vertex_order;i++)
+		while(mem[i]<count[i]) add_memory(vc,i);
+	while(current_vertices<p_) add_memory_vertices(vc);
+	up=0;p=p_;
+	for(i=0;i<p;i++) {
+		n=nu[i]=nu_[i];
+		q=mep[n]+((n<<1)+1)*mec[n];
+		vc.n_set_pointer(i,n);
+		mec[n]++;
+		for(j=0;j<n;j++) {
+			q[j]=*(ed_++);
+			if(q[j]<0||q[j]>=p) voro_fatal_error("Vertex index is out of range",VOROPP_INTERNAL_ERROR);
+		}
+		q[n<<1]=i;
+		ed[i]=q;
+		pts[i<<2]=2*v[3*i];
+		pts[(i<<2)+1]=2*v[3*i+1];
+		pts[(i<<2)+2]=2*v[3*i+2];
+	}
+	construct_relations();
+	if(!check_relations(false)||!check_duplicates(false))
+		voro_fatal_error("Invalid polyhedron",VOROPP_INTERNAL_ERROR);
+}
+
+/** Initializes the cell to be an arbitrary convex polyhedron. It calls the
+ * base class initialization routine to set up the edge and vertex information,
+ * and then sets up the neighbor information.
+ * \param[in] p_ the number of vertices.
+ * \param[in] v the coordinates of the vertices.
+ * \param[in] nu_ the order of each vertex.
+ * \param[in] ed_ the neighbors of each vertex, listed consecutively.
+ * \param[in] ne_ the neighbor information for each edge of each vertex,
+ *                listed consecutively. */
+void voronoicell_neighbor::init_polyhedron(int p_,const double *v,const int *nu_,const int *ed_,const int *ne_) {
+	init_polyhedron_base(*this,p_,v,nu_,ed_);
+	for(int i=0;i<p;i++) for(int j=0;j<nu[i];j++) ne[i][j]=*(ne_++);
+}
+
 /** Checks that the relational table of the Voronoi cell is accurate, and
  * prints out any errors. This algorithm is O(p), so running it every time the
- * plane routine is called will result in a significant slowdown. */
-void voronoicell_base::check_relations() {
+ * plane routine is called will result in a significant slowdown.
+ * \param[in] verbose whether to print out the errors.
+ * \return True if the relational table is accurate, false otherwise. */
+bool voronoicell_base::check_relations(bool verbose) {
 	int i,j;
-	for(i=0;i<p;i++) for(j=0;j<nu[i];j++) if(ed[ed[i][j]][ed[i][nu[i]+j]]!=i)
-		printf("Relational error at point %d, edge %d.\n",i,j);
+	bool ok=true;
+	for(i=0;i<p;i++) for(j=0;j<nu[i];j++) if(ed[ed[i][j]][ed[i][nu[i]+j]]!=i) {
+		if(verbose) printf("Relational error at point %d, edge %d.\n",i,j);
+		ok=false;
+	}
+	return ok;
 }
 
 /** This routine checks for any two vertices that are connected by more than
  * one edge. The plane algorithm is designed so that this should not happen, so
  * any occurrences are most likely errors. Note that the routine is O(p), so
  * running it every time the plane routine is called will result in a
- * significant slowdown. */
-void voronoicell_base::check_duplicates() {
+ * significant slowdown.
+ * \param[in] verbose whether to print out the duplicate edges.
+ * \return True if there are no duplicate edges, false otherwise. */
+bool voronoicell_base::check_duplicates(bool verbose) {
 	int i,j,k;
-	for(i=0;i<p;i++) for(j=1;j<nu[i];j++) for(k=0;k<j;k++) if(ed[i][j]==ed[i][k])
-		printf("Duplicate edges: (%d,%d) and (%d,%d) [%d]\n",i,j,i,k,ed[i][j]);
+	bool ok=true;
+	for(i=0;i<p;i++) for(j=1;j<nu[i];j++) for(k=0;k<j;k++) if(ed[i][j]==ed[i][k]) {
+		if(verbose) printf("Duplicate edges: (%d,%d) and (%d,%d) [%d]\n",i,j,i,k,ed[i][j]);
+		ok=false;
+	}
+	return ok;
 }
 
 /** Constructs the relational table if the edges have been specified. */
@@ -1516,8 +1600,7 @@ bool voronoicell_base::delete_connection(vc_class &vc,int j,int k,bool hand) {
  * search routine to fail. In the fall-back routine, we just test every edge to
  * find one straddling the plane. */
 bool voronoicell_base::failsafe_find(int &lp,int &ls,int &us,double &l,double &u) {
-	fputs("Bailed out of convex calculation (not supported yet)\n",stderr);
-	exit(1);
+	voro_fatal_error("Bailed out of convex calculation (not supported yet)",VOROPP_INTERNAL_ERROR);
 /*	qw=1;lw=0;
 	for(qp=0;qp<p;qp++) {
 		qw=m_test(qp,q);
@@ -1905,6 +1988,7 @@ void voronoicell_base::centroid(double &cx,double &cy,double &cz) {
  * all planes that could cut the cell have been considered.
  * \return The maximum radius squared of a vertex.*/
 double voronoicell_base::max_radius_squared() {
+	if(p==0) return 0;
 	double r,s,*ptsp=pts+4,*ptse=pts+(p<<2);
 	r=*pts*(*pts)+pts[1]*pts[1]+pts[2]*pts[2];
 	while(ptsp<ptse) {
@@ -2710,5 +2794,7 @@ template bool voronoicell_base::nplane(voronoicell&,double,double,double,double,
 template bool voronoicell_base::nplane(voronoicell_neighbor&,double,double,double,double,int);
 template void voronoicell_base::check_memory_for_copy(voronoicell&,voronoicell_base*);
 template void voronoicell_base::check_memory_for_copy(voronoicell_neighbor&,voronoicell_base*);
+template void voronoicell_base::init_polyhedron_base(voronoicell&,int,const double*,const int*,const int*);
+template void voronoicell_base::init_polyhedron_base(voronoicell_neighbor&,int,const double*,const int*,const int*);
 
 }
@@ -200,8 +200,15 @@ class voronoicell_base {
 		bool plane_intersects(double x,double y,double z,double rsq);
 		bool plane_intersects_guess(double x,double y,double z,double rsq);
 		void construct_relations();
-		void check_relations();
-		void check_duplicates();
+		/** Empties the cell, leaving it with no vertices. This is used
+		 * to discard a cell which became inconsistent after an error
+		 * interrupted a plane cut. */
+		inline void clear() {
+			for(int i=0;i<current_vertex_order;i++) mec[i]=0;
+			p=0;up=0;
+		}
+		bool check_relations(bool verbose=true);
+		bool check_duplicates(bool verbose=true);
 		void print_edges();
 		/** Returns a list of IDs of neighboring particles
 		 * corresponding to each face.
@@ -253,6 +260,8 @@ class voronoicell_base {
 		template<class vc_class>
 		void check_memory_for_copy(vc_class &vc,voronoicell_base* vb);
 		void copy(voronoicell_base* vb);
+		template<class vc_class>
+		void init_polyhedron_base(vc_class &vc,int p_,const double *v,const int *nu_,const int *ed_);
 	private:
 		/** This is the delete stack, used to store the vertices which
 		 * are going to be deleted during the plane cutting procedure.
@@ -315,6 +324,8 @@ class voronoicell_base {
 		friend class voronoicell_neighbor;
 };
 
+class voronoicell_neighbor;
+
 /** \brief Extension of the voronoicell_base class to represent a Voronoi
  * cell without neighbor information.
  *
@@ -335,6 +346,7 @@ class voronoicell : public voronoicell_base {
 			voronoicell_base* vb((voronoicell_base*) &c);
 			check_memory_for_copy(*this,vb);copy(vb);
 		}
+		void operator=(voronoicell_neighbor &c);
 		/** Cuts a Voronoi cell using by the plane corresponding to the
 		 * perpendicular bisector of a particle.
 		 * \param[in] (x,y,z) the position of the particle.
@@ -399,6 +411,15 @@ class voronoicell : public voronoicell_base {
 		inline void init_tetrahedron(double x0,double y0,double z0,double x1,double y1,double z1,double x2,double y2,double z2,double x3,double y3,double z3) {
 			init_tetrahedron_base(x0,y0,z0,x1,y1,z1,x2,y2,z2,x3,y3,z3);
 		}
+		/** Initializes the cell to be an arbitrary convex polyhedron.
+		 * \param[in] p_ the number of vertices.
+		 * \param[in] v the coordinates of the vertices.
+		 * \param[in] nu_ the order of each vertex.
+		 * \param[in] ed_ the neighbors of each vertex, listed
+		 *                consecutively. */
+		inline void init_polyhedron(int p_,const double *v,const int *nu_,const int *ed_) {
+			init_polyhedron_base(*this,p_,v,nu_,ed_);
+		}
 		void init_l_shape();
 	private:
 		inline void n_allocate(int i,int m) {};
@@ -504,6 +525,7 @@ class voronoicell_neighbor : public voronoicell_base {
 		void init(double xmin,double xmax,double ymin,double ymax,double zmin,double zmax);
 		void init_octahedron(double l);
 		void init_tetrahedron(double x0,double y0,double z0,double x1,double y1,double z1,double x2,double y2,double z2,double x3,double y3,double z3);
+		void init_polyhedron(int p_,const double *v,const int *nu_,const int *ed_,const int *ne_);
 		void check_facets();
 		virtual void neighbors(std::vector<int> &v);
 		virtual void print_edges_neighbors(int i);
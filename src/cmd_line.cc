@@ -163,7 +163,7 @@ void cmd_line_output(c_loop &vl,c_class &con,const char* format,FILE* outfile,FI
 	if(verbose) tp=con.total_particles();
 }
 
-int main(int argc,char **argv) {
+int voro_main(int argc,char **argv) {
 	int i=1,j=-7,custom_output=0,nx,ny,nz,init_mem(8);
 	double ls=0;
 	blocks_mode bm=none;
@@ -495,3 +495,15 @@ int main(int argc,char **argv) {
 	if(povv_file!=NULL) fclose(povv_file);
 	return 0;
 }
+
+int main(int argc,char **argv) {
+
+	// Report any fatal errors from the library, and exit with their
+	// status code
+	try {
+		return voro_main(argc,argv);
+	} catch(voro_error &e) {
+		fprintf(stderr,"voro++: %s\n",e.what());
+		return e.status;
+	}
+}
@@ -14,19 +14,19 @@ namespace voro {
 void check_duplicate(int n,double x,double y,double z,int id,double *qp) {
 	double dx=*qp-x,dy=qp[1]-y,dz=qp[2]-z;
 	if(dx*dx+dy*dy+dz*dz<1e-10) {
-		printf("Duplicate: %d (%g,%g,%g) matches %d (%g,%g,%g)\n",n,x,y,z,id,*qp,qp[1],qp[2]);
-		exit(1);
+		char buf[256];
+		snprintf(buf,256,"Duplicate: %d (%g,%g,%g) matches %d (%g,%g,%g)",n,x,y,z,id,*qp,qp[1],qp[2]);
+		voro_fatal_error(buf,VOROPP_DUPLICATE_ERROR);
 	}
 }
 
-/** \brief Function for printing fatal error messages and exiting.
+/** \brief Function for reporting fatal errors.
  *
- * Function for printing fatal error messages and exiting.
- * \param[in] p a pointer to the message to print.
- * \param[in] status the status code to return with. */
+ * Function for reporting fatal errors, by throwing a voro_error exception.
+ * \param[in] p a pointer to the error message.
+ * \param[in] status the status code of the error. */
 void voro_fatal_error(const char *p,int status) {
-	fprintf(stderr,"voro++: %s\n",p);
-	exit(status);
+	throw voro_error(p,status);
 }
 
 /** \brief Prints a vector of positions.
@@ -53,8 +53,9 @@ void voro_print_positions(std::vector<double> &v,FILE *fp) {
 FILE* safe_fopen(const char *filename,const char *mode) {
 	FILE *fp=fopen(filename,mode);
 	if(fp==NULL) {
-		fprintf(stderr,"voro++: Unable to open file '%s'\n",filename);
-		exit(VOROPP_FILE_ERROR);
+		char buf[256];
+		snprintf(buf,256,"Unable to open file '%s'",filename);
+		voro_fatal_error(buf,VOROPP_FILE_ERROR);
 	}
 	return fp;
 }
@@ -75,7 +76,7 @@ void voro_print_vector(std::vector<int> &v,FILE *fp) {
 		else fprintf(fp,"%d %d %d",v[k],v[k+1],v[k+2]);
 	} else {
 		if(k+2==s) fprintf(fp,"%d %d",v[k],v[k+1]);
-		else fprintf(fp,"%d",v[k]);
+		else if(k<s) fprintf(fp,"%d",v[k]);
 	}
 }
 
@@ -95,7 +96,7 @@ void voro_print_vector(std::vector<double> &v,FILE *fp) {
 		else fprintf(fp,"%g %g %g",v[k],v[k+1],v[k+2]);
 	} else {
 		if(k+2==s) fprintf(fp,"%g %g",v[k],v[k+1]);
-		else fprintf(fp,"%g",v[k]);
+		else if(k<s) fprintf(fp,"%g",v[k]);
 	}
 }
 
@@ -12,12 +12,26 @@
 
 #include <cstdio>
 #include <cstdlib>
+#include <stdexcept>
 #include <vector>
 
 #include "config.hh"
 
 namespace voro {
 
+/** \brief The exception thrown by Voro++ when it encounters a fatal error.
+ *
+ * The exception thrown by Voro++ when it encounters a fatal error, so that
+ * programs which use the library can recover instead of being terminated. */
+class voro_error : public std::runtime_error {
+	public:
+		/** The status code of the error, one of the VOROPP_*_ERROR
+		 * codes from config.hh. */
+		const int status;
+		voro_error(const char *p,int status_)
+			: std::runtime_error(p), status(status_) {}
+};
+
 void check_duplicate(int n,double x,double y,double z,int id,double *qp);
 
 void voro_fatal_error(const char *p,int status);
@@ -118,6 +118,10 @@ const double optimal_particles=5.6;
  * arguments passed to the command line utility. */
 #define VOROPP_CMD_LINE_ERROR 4
 
+/** Voro++ returns this status code if a particle is put into a periodic
+ * container at the same position as another particle. */
+#define VOROPP_DUPLICATE_ERROR 5
+
 }
 
 #endif
@@ -17,9 +17,29 @@ features.
 Voro++ is written in C++ and this rust crate provides API bindings to a limited
 subset of the voro++ library. */
 
+#![recursion_limit = "512"]
+
 // Rust API bindings written by David McDougall, 2020. Email Address: dam1784@rit.edu
 
 mod rust;
+pub use rust::cell_geometry::CellGeometry;
+pub use rust::cell_stats::{CellStats, CellStatsBuilder};
+pub use rust::container::Container;
+pub use rust::container_base::{Cells, ContainerBase};
+pub use rust::container_periodic::ContainerPeriodic;
+pub use rust::container_periodic_poly::ContainerPeriodicPoly;
+pub use rust::container_poly::ContainerPoly;
+pub use rust::custom_format::{CustomFormat, CONTROL_SEQUENCES};
+pub use rust::error::Error;
+pub use rust::io;
+#[cfg(feature = "rayon")]
+pub use rust::parallel::{ContainerParallel, ParticleCell};
+pub use rust::positioned_cell::PositionedCell;
+pub use rust::unit_cell::UnitCell;
 pub use rust::voronoi_cell::VoronoiCell;
-pub use rust::voronoi_cell_base::VoronoiCellBase;
+pub use rust::voronoi_cell_base::{
+    radical_plane_rsq, Minkowski, VoronoiCellBase, UNKNOWN_NEIGHBOR,
+};
 pub use rust::voronoi_cell_neighbor::VoronoiCellNeighbor;
+pub use rust::voronoi_index::VoronoiIndex;
+pub use rust::wall::{Wall, WallCone, WallCylinder, WallPlane, WallSphere};
@@ -0,0 +1,62 @@
+use cpp::cpp;
+use std::io::{self, Write};
+
+cpp! {{
+    #include <cstdio>
+}}
+
+/// Runs a function which writes to a C file handle ("FILE*"), and copies all
+/// of its output into a Rust writer. The output goes through a temporary file
+/// which is made by the C function "tmpfile", and is deleted afterwards. The
+/// output is discarded if the function returns an error.
+pub(crate) fn write_via_c_file<W, F>(w: &mut W, f: F) -> io::Result<()>
+where
+    W: Write + ?Sized,
+    F: FnOnce(*mut std::ffi::c_void) -> io::Result<()>,
+{
+    let fp = cpp!(unsafe [] -> *mut std::ffi::c_void as "FILE*" {
+        return std::tmpfile();
+    });
+    if fp.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    let result = f(fp).and_then(|()| copy(fp, w));
+    cpp!(unsafe [fp as "FILE*"] {
+        std::fclose(fp);
+    });
+    result
+}
+
+/// Copies the contents of a C file handle into a Rust writer, starting from
+/// the beginning of the file.
+fn copy<W: Write + ?Sized>(fp: *mut std::ffi::c_void, w: &mut W) -> io::Result<()> {
+    let rewound = cpp!(unsafe [fp as "FILE*"] -> bool as "bool" {
+        return std::fflush(fp) == 0 && !std::ferror(fp) && std::fseek(fp, 0, SEEK_SET) == 0;
+    });
+    if !rewound {
+        return Err(io::Error::last_os_error());
+    }
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let data = buffer.as_mut_ptr();
+        let capacity = buffer.len();
+        let len = cpp!(unsafe [fp as "FILE*", data as "unsigned char*", capacity as "size_t"]
+                -> usize as "size_t" {
+            return std::fread(data, 1, capacity, fp);
+        });
+        if len == 0 {
+            break;
+        }
+        w.write_all(&buffer[..len])?;
+    }
+    let failed = cpp!(unsafe [fp as "FILE*"] -> bool as "bool" {
+        return std::ferror(fp);
+    });
+    if failed {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "failed to read the temporary file",
+        ));
+    }
+    w.flush()
+}
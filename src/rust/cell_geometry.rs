@@ -0,0 +1,204 @@
+use cpp::cpp;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+
+    inline bool voroxx_neighbors(voronoicell &c, std::vector<int> &v) {
+        return false;
+    }
+
+    inline bool voroxx_neighbors(voronoicell_neighbor &c, std::vector<int> &v) {
+        c.neighbors(v);
+        return true;
+    }
+
+    // Copies all of the information about a Voronoi cell into a Rust
+    // "CellGeometry".
+    template<class v_cell>
+    void voroxx_cell_geometry(v_cell &c, void *geometry) {
+        std::vector<double> vertices;
+        std::vector<int> faces, neighbors;
+        c.vertices(vertices);
+        c.face_vertices(faces);
+        bool has_neighbors = voroxx_neighbors(c, neighbors);
+        double volume = c.volume();
+        double area = c.surface_area();
+        double centroid[3];
+        c.centroid(centroid[0], centroid[1], centroid[2]);
+        double *vertices_data = vertices.data();
+        int *faces_data = faces.data();
+        int *neighbors_data = neighbors.data();
+        size_t num_vertices = vertices.size() / 3;
+        size_t faces_len = faces.size();
+        size_t num_neighbors = neighbors.size();
+        double *centroid_data = centroid;
+        rust!(voroxx_set_cell_geometry [geometry: &mut CellGeometry as "void*",
+                vertices_data: *const [f64; 3] as "double*", num_vertices: usize as "size_t",
+                faces_data: *const i32 as "int*", faces_len: usize as "size_t",
+                has_neighbors: bool as "bool",
+                neighbors_data: *const i32 as "int*", num_neighbors: usize as "size_t",
+                volume: f64 as "double", area: f64 as "double",
+                centroid_data: *const [f64; 3] as "double*"] {
+            unsafe fn slice<'a, T>(data: *const T, len: usize) -> &'a [T] {
+                if len == 0 { &[] } else { std::slice::from_raw_parts(data, len) }
+            }
+            geometry.vertices = slice(vertices_data, num_vertices).to_vec();
+            let mut faces = slice(faces_data, faces_len).iter();
+            geometry.faces.clear();
+            while let Some(&n) = faces.next() {
+                geometry.faces.push(faces.by_ref().take(n as usize).map(|&v| v as usize).collect());
+            }
+            geometry.neighbors = if has_neighbors {
+                Some(slice(neighbors_data, num_neighbors).to_vec())
+            } else {
+                None
+            };
+            geometry.volume = volume;
+            geometry.surface_area = area;
+            geometry.centroid = *centroid_data;
+        });
+    }
+}}
+
+/// A snapshot of the geometry of a Voronoi cell, which is stored as plain Rust
+/// data.
+///
+/// Use the method `VoronoiCellBase::geometry` to make one. Unlike the Voronoi
+/// cells, reading from a snapshot does not call into the C++ library, and it
+/// can be shared between threads. With the cargo feature `serde`, it can be
+/// serialized and deserialized.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellGeometry {
+    /// The vertex coordinates, in the local coordinate system of the cell.
+    pub vertices: Vec<[f64; 3]>,
+
+    /// The vertices of each face, as indices into the vertices list. The
+    /// vertices of each face are ordered clockwise, as seen from outside of
+    /// the cell.
+    pub faces: Vec<Vec<usize>>,
+
+    /// The ID of the neighbor for each face, in the same order as the faces,
+    /// or None if the cell does not track neighbor information.
+    pub neighbors: Option<Vec<i32>>,
+
+    /// The volume of the cell.
+    pub volume: f64,
+
+    /// The total surface area of the cell.
+    pub surface_area: f64,
+
+    /// The centroid of the cell, in the local coordinate system of the cell.
+    pub centroid: [f64; 3],
+}
+
+impl CellGeometry {
+    /// Returns the number of faces of the cell.
+    pub fn number_of_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Returns the number of vertices of the cell.
+    pub fn number_of_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Counts the number of edges of the cell.
+    pub fn number_of_edges(&self) -> usize {
+        self.faces.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    /// Calculates the area of each face, in the same order as the faces.
+    pub fn face_areas(&self) -> Vec<f64> {
+        self.faces
+            .iter()
+            .map(|face| {
+                let mut n = [0.0; 3];
+                for (i, &a) in face.iter().enumerate() {
+                    let a = &self.vertices[a];
+                    let b = &self.vertices[face[(i + 1) % face.len()]];
+                    n[0] += a[1] * b[2] - a[2] * b[1];
+                    n[1] += a[2] * b[0] - a[0] * b[2];
+                    n[2] += a[0] * b[1] - a[1] * b[0];
+                }
+                0.5 * (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt()
+            })
+            .collect()
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::{VoronoiCell, VoronoiCellBase, VoronoiCellNeighbor};
+    fn is_send_sync<T: Send + Sync>() {}
+    is_send_sync::<CellGeometry>();
+
+    let mut x = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    x.plane(&[1.0, 1.0, 1.0]).unwrap();
+    let g = x.geometry();
+    assert!(g.vertices == x.vertices());
+    assert!(g.faces == x.face_vertices());
+    assert!(g.neighbors.is_none());
+    assert!(g.volume == x.volume());
+    assert!(g.surface_area == x.surface_area());
+    assert!(g.centroid == x.centroid());
+    assert!(g.number_of_faces() == x.number_of_faces() as usize);
+    assert!(g.number_of_vertices() == x.number_of_vertices() as usize);
+    assert!(g.number_of_edges() == x.number_of_edges() as usize);
+    let areas = x.face_areas();
+    assert!(g.face_areas().len() == areas.len());
+    for (a, b) in g.face_areas().iter().zip(&areas) {
+        assert!((a - b).abs() < 1e-12);
+    }
+    for face in &g.faces {
+        let [a, b, c] = [
+            g.vertices[face[0]],
+            g.vertices[face[1]],
+            g.vertices[face[2]],
+        ];
+        let (u, v) = (
+            [b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+            [c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+        );
+        let n = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        let d = [
+            a[0] - g.centroid[0],
+            a[1] - g.centroid[1],
+            a[2] - g.centroid[2],
+        ];
+        assert!(n[0] * d[0] + n[1] * d[1] + n[2] * d[2] < 0.0);
+    }
+
+    let mut x = VoronoiCellNeighbor::init(&[-1.0; 3], &[1.0; 3]);
+    x.nplane(&[1.0, 1.0, 1.0], 42).unwrap();
+    let g = x.geometry();
+    assert!(g.faces == x.face_vertices());
+    assert!(g.neighbors == Some(x.neighbors()));
+    assert!(g.neighbors.as_ref().unwrap().contains(&42));
+    assert!(g.clone() == g);
+}
+
+/// Round trip a snapshot through JSON.
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use crate::{VoronoiCellBase, VoronoiCellNeighbor};
+    let mut x = VoronoiCellNeighbor::init_octahedron(1.0);
+    x.nplane(&[0.5, 0.0, 0.0], 7).unwrap();
+    let g = x.geometry();
+    let json = serde_json::to_string(&g).unwrap();
+    let h: CellGeometry = serde_json::from_str(&json).unwrap();
+    assert!(g.faces == h.faces);
+    assert!(g.neighbors == h.neighbors);
+    assert!((g.volume - h.volume).abs() < 1e-12);
+    assert!(g.vertices.len() == h.vertices.len());
+    for (a, b) in g.vertices.iter().zip(&h.vertices) {
+        assert!((0..3).all(|i| (a[i] - b[i]).abs() < 1e-12));
+    }
+}
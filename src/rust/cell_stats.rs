@@ -0,0 +1,481 @@
+use crate::rust::container_base::ContainerBase;
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell_base::VoronoiCellBase;
+use cpp::cpp;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+
+    // Computes the requested statistics of a Voronoi cell, and copies them
+    // into a Rust "CellStats". Each statistic is requested by the character
+    // of its control sequence in the custom output format.
+    template<class v_cell>
+    void voroxx_cell_stats(v_cell &c, const char *codes, size_t num_codes, void *stats) {
+        std::vector<int> vi;
+        std::vector<double> vd;
+        for(size_t i = 0; i < num_codes; i++) {
+            char code = codes[i];
+            vi.clear();
+            vd.clear();
+            switch(code) {
+                case 'w': vi.push_back(c.p); break;
+                case 'p': c.vertices(vd); break;
+                case 'o': c.vertex_orders(vi); break;
+                case 'm': vd.push_back(0.25 * c.max_radius_squared()); break;
+                case 'g': vi.push_back(c.number_of_edges()); break;
+                case 'E': vd.push_back(c.total_edge_distance()); break;
+                case 'e': c.face_perimeters(vd); break;
+                case 's': vi.push_back(c.number_of_faces()); break;
+                case 'F': vd.push_back(c.surface_area()); break;
+                case 'A': c.face_freq_table(vi); break;
+                case 'a': c.face_orders(vi); break;
+                case 'f': c.face_areas(vd); break;
+                case 't': c.face_vertices(vi); break;
+                case 'l': c.normals(vd); break;
+                case 'n':
+                    if(!voroxx_neighbors(c, vi)) continue;
+                    break;
+                case 'v': vd.push_back(c.volume()); break;
+                case 'c': {
+                    double cx, cy, cz;
+                    c.centroid(cx, cy, cz);
+                    vd.push_back(cx);
+                    vd.push_back(cy);
+                    vd.push_back(cz);
+                } break;
+                default: continue;
+            }
+            int *ints = vi.data();
+            size_t num_ints = vi.size();
+            double *doubles = vd.data();
+            size_t num_doubles = vd.size();
+            rust!(voroxx_set_cell_stat [stats: &mut CellStats as "void*", code: u8 as "char",
+                    ints: *const i32 as "int*", num_ints: usize as "size_t",
+                    doubles: *const f64 as "double*", num_doubles: usize as "size_t"] {
+                stats.set(code as char, slice(ints, num_ints), slice(doubles, num_doubles));
+            });
+        }
+    }
+}}
+
+unsafe fn slice<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+/// The statistics about a particle and its Voronoi cell, which is stored as
+/// plain Rust data.
+///
+/// Use a `CellStatsBuilder` to select which statistics to compute. Each
+/// statistic is None unless it was selected. Every control sequence of the
+/// custom output format (see `CONTROL_SEQUENCES`) has a corresponding field
+/// or method, which is listed in its documentation. With the cargo feature
+/// `serde`, it can be serialized and deserialized.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellStats {
+    /// The ID of the particle. ("%i")
+    pub id: i32,
+
+    /// The position of the particle. ("%x", "%y", "%z", "%q")
+    pub position: [f64; 3],
+
+    /// The radius of the particle. ("%r")
+    pub radius: f64,
+
+    /// The number of vertices of the cell. ("%w")
+    pub number_of_vertices: Option<i32>,
+
+    /// The vertex coordinates, in the local coordinate system of the cell.
+    /// ("%p", see also method `global_vertices` for "%P")
+    pub vertices: Option<Vec<[f64; 3]>>,
+
+    /// The order of each vertex, which is the number of edges that meet at
+    /// the vertex. ("%o")
+    pub vertex_orders: Option<Vec<i32>>,
+
+    /// The maximum distance squared from the particle to a vertex. ("%m")
+    pub max_radius_squared: Option<f64>,
+
+    /// The number of edges of the cell. ("%g")
+    pub number_of_edges: Option<i32>,
+
+    /// The total length of the edges of the cell. ("%E")
+    pub total_edge_distance: Option<f64>,
+
+    /// The perimeter of each face. ("%e")
+    pub face_perimeters: Option<Vec<f64>>,
+
+    /// The number of faces of the cell. ("%s")
+    pub number_of_faces: Option<i32>,
+
+    /// The total surface area of the cell. ("%F")
+    pub surface_area: Option<f64>,
+
+    /// The frequency table of the face orders. The entry at index `n` is the
+    /// number of faces which have `n` edges. ("%A")
+    pub face_freq_table: Option<Vec<i32>>,
+
+    /// The number of edges of each face. ("%a")
+    pub face_orders: Option<Vec<i32>>,
+
+    /// The area of each face. ("%f")
+    pub face_areas: Option<Vec<f64>>,
+
+    /// The vertices of each face, as indices into the vertices list. ("%t")
+    pub face_vertices: Option<Vec<Vec<usize>>>,
+
+    /// The outward unit normal vector of each face. ("%l")
+    pub normals: Option<Vec<[f64; 3]>>,
+
+    /// The ID of the neighbor for each face. This is None if it was not
+    /// selected, or if the cell does not track neighbor information. ("%n")
+    pub neighbors: Option<Vec<i32>>,
+
+    /// The volume of the cell. ("%v")
+    pub volume: Option<f64>,
+
+    /// The centroid of the cell, in the local coordinate system of the cell.
+    /// ("%c", see also method `global_centroid` for "%C")
+    pub centroid: Option<[f64; 3]>,
+}
+
+impl CellStats {
+    /// Makes a builder which selects no statistics.
+    pub fn builder() -> CellStatsBuilder {
+        CellStatsBuilder::new()
+    }
+
+    /// Returns the vertex coordinates in the global coordinate system, by
+    /// adding the position of the particle to each vertex. ("%P")
+    pub fn global_vertices(&self) -> Option<Vec<[f64; 3]>> {
+        self.vertices
+            .as_ref()
+            .map(|v| v.iter().map(|v| add(v, &self.position)).collect())
+    }
+
+    /// Returns the centroid in the global coordinate system. ("%C")
+    pub fn global_centroid(&self) -> Option<[f64; 3]> {
+        self.centroid.as_ref().map(|c| add(c, &self.position))
+    }
+
+    /// Stores one statistic, which the C++ function `voroxx_cell_stats`
+    /// computed.
+    fn set(&mut self, code: char, ints: &[i32], doubles: &[f64]) {
+        match code {
+            'w' => self.number_of_vertices = Some(ints[0]),
+            'p' => self.vertices = Some(triples(doubles)),
+            'o' => self.vertex_orders = Some(ints.to_vec()),
+            'm' => self.max_radius_squared = Some(doubles[0]),
+            'g' => self.number_of_edges = Some(ints[0]),
+            'E' => self.total_edge_distance = Some(doubles[0]),
+            'e' => self.face_perimeters = Some(doubles.to_vec()),
+            's' => self.number_of_faces = Some(ints[0]),
+            'F' => self.surface_area = Some(doubles[0]),
+            'A' => self.face_freq_table = Some(ints.to_vec()),
+            'a' => self.face_orders = Some(ints.to_vec()),
+            'f' => self.face_areas = Some(doubles.to_vec()),
+            't' => {
+                let mut faces = vec![];
+                let mut data = ints.iter();
+                while let Some(&n) = data.next() {
+                    faces.push(
+                        data.by_ref()
+                            .take(n as usize)
+                            .map(|&v| v as usize)
+                            .collect(),
+                    );
+                }
+                self.face_vertices = Some(faces);
+            }
+            'l' => self.normals = Some(triples(doubles)),
+            'n' => self.neighbors = Some(ints.to_vec()),
+            'v' => self.volume = Some(doubles[0]),
+            'c' => self.centroid = Some([doubles[0], doubles[1], doubles[2]]),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Selects which statistics a `CellStats` contains, and computes them.
+///
+/// All of the selected statistics of a cell are computed with a single call
+/// into the C++ library. The statistics of the particle (ID, position, and
+/// radius) are always included.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CellStatsBuilder {
+    /// The control sequence characters of the selected statistics.
+    codes: String,
+}
+
+impl CellStatsBuilder {
+    /// Makes a builder which selects no statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes a builder which selects every statistic.
+    pub fn all() -> Self {
+        Self {
+            codes: STATISTICS.iter().collect(),
+        }
+    }
+
+    /// Makes a builder which selects every statistic that the given custom
+    /// output format uses.
+    pub fn from_format(format: &CustomFormat) -> Self {
+        format
+            .control_sequences()
+            .fold(Self::new(), |builder, code| match code {
+                'P' => builder.select('p'),
+                'C' => builder.select('c'),
+                _ => builder.select(code),
+            })
+    }
+
+    /// Selects the number of vertices.
+    pub fn number_of_vertices(self) -> Self {
+        self.select('w')
+    }
+
+    /// Selects the vertex coordinates.
+    pub fn vertices(self) -> Self {
+        self.select('p')
+    }
+
+    /// Selects the order of each vertex.
+    pub fn vertex_orders(self) -> Self {
+        self.select('o')
+    }
+
+    /// Selects the maximum radius squared of a vertex.
+    pub fn max_radius_squared(self) -> Self {
+        self.select('m')
+    }
+
+    /// Selects the number of edges.
+    pub fn number_of_edges(self) -> Self {
+        self.select('g')
+    }
+
+    /// Selects the total edge distance.
+    pub fn total_edge_distance(self) -> Self {
+        self.select('E')
+    }
+
+    /// Selects the perimeter of each face.
+    pub fn face_perimeters(self) -> Self {
+        self.select('e')
+    }
+
+    /// Selects the number of faces.
+    pub fn number_of_faces(self) -> Self {
+        self.select('s')
+    }
+
+    /// Selects the total surface area.
+    pub fn surface_area(self) -> Self {
+        self.select('F')
+    }
+
+    /// Selects the frequency table of the face orders.
+    pub fn face_freq_table(self) -> Self {
+        self.select('A')
+    }
+
+    /// Selects the number of edges of each face.
+    pub fn face_orders(self) -> Self {
+        self.select('a')
+    }
+
+    /// Selects the area of each face.
+    pub fn face_areas(self) -> Self {
+        self.select('f')
+    }
+
+    /// Selects the vertices of each face.
+    pub fn face_vertices(self) -> Self {
+        self.select('t')
+    }
+
+    /// Selects the normal vector of each face.
+    pub fn normals(self) -> Self {
+        self.select('l')
+    }
+
+    /// Selects the neighbor of each face.
+    pub fn neighbors(self) -> Self {
+        self.select('n')
+    }
+
+    /// Selects the volume.
+    pub fn volume(self) -> Self {
+        self.select('v')
+    }
+
+    /// Selects the centroid.
+    pub fn centroid(self) -> Self {
+        self.select('c')
+    }
+
+    /// Tests whether the neighbor information is selected.
+    pub fn uses_neighbors(&self) -> bool {
+        self.selects('n')
+    }
+
+    /// Tests whether the statistic with the given control sequence character
+    /// is selected.
+    pub(crate) fn selects(&self, code: char) -> bool {
+        self.codes.contains(code)
+    }
+
+    /// Computes the selected statistics of a Voronoi cell.
+    ///
+    /// __Parameters:__
+    /// * `cell` The Voronoi cell.
+    /// * `id` The ID of the particle.
+    /// * `xyz` The position of the particle.
+    /// * `radius` The radius of the particle.
+    pub fn compute<C: VoronoiCellBase + ?Sized>(
+        &self,
+        cell: &C,
+        id: i32,
+        xyz: &[f64; 3],
+        radius: f64,
+    ) -> CellStats {
+        let mut stats = CellStats {
+            id,
+            position: *xyz,
+            radius,
+            ..CellStats::default()
+        };
+        cell.cell_stats(self.codes.as_bytes(), &mut stats);
+        stats
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// their selected statistics. The cells are computed with neighbor
+    /// information only if it is selected.
+    ///
+    /// __Returns:__
+    ///     The statistics of each particle, in the order of the method
+    ///     `ContainerBase::cells`, or an error if the computation of a cell
+    ///     failed.
+    pub fn compute_container<T: ContainerBase>(
+        &self,
+        container: &T,
+    ) -> Result<Vec<CellStats>, Error> {
+        if self.uses_neighbors() {
+            container
+                .cells_neighbor()
+                .map(|r| r.map(|(id, xyz, radius, cell)| self.compute(&cell, id, &xyz, radius)))
+                .collect()
+        } else {
+            container
+                .cells()
+                .map(|r| r.map(|(id, xyz, radius, cell)| self.compute(&cell, id, &xyz, radius)))
+                .collect()
+        }
+    }
+
+    fn select(mut self, code: char) -> Self {
+        if STATISTICS.contains(&code) && !self.codes.contains(code) {
+            self.codes.push(code);
+        }
+        self
+    }
+}
+
+/// The control sequences which have a field in `CellStats`, excluding the
+/// statistics of the particle.
+pub(crate) const STATISTICS: &[char] = &[
+    'w', 'p', 'o', 'm', 'g', 'E', 'e', 's', 'F', 'A', 'a', 'f', 't', 'l', 'n', 'v', 'c',
+];
+
+fn add(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn triples(data: &[f64]) -> Vec<[f64; 3]> {
+    data.chunks_exact(3).map(|v| [v[0], v[1], v[2]]).collect()
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::{Container, VoronoiCell, VoronoiCellNeighbor};
+    fn is_send_sync<T: Send + Sync>() {}
+    is_send_sync::<CellStats>();
+    is_send_sync::<CellStatsBuilder>();
+
+    let cube = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let stats = CellStats::builder()
+        .volume()
+        .centroid()
+        .compute(&cube, 3, &[1.0, 2.0, 3.0], 0.5);
+    assert!(stats.id == 3);
+    assert!(stats.volume == Some(8.0));
+    assert!(stats.centroid == Some([0.0; 3]));
+    assert!(stats.global_centroid() == Some([1.0, 2.0, 3.0]));
+    assert!(stats.surface_area.is_none() && stats.vertices.is_none());
+
+    let stats = CellStatsBuilder::all().compute(&cube, 0, &[0.0; 3], 0.5);
+    assert!(stats.number_of_vertices == Some(8));
+    assert!(stats.vertices.as_ref().unwrap().len() == 8);
+    assert!(stats.global_vertices() == stats.vertices);
+    assert!(stats.vertex_orders == Some(vec![3; 8]));
+    assert!(stats.max_radius_squared == Some(3.0));
+    assert!(stats.number_of_edges == Some(12));
+    assert!(stats.total_edge_distance == Some(24.0));
+    assert!(stats.face_perimeters == Some(vec![8.0; 6]));
+    assert!(stats.number_of_faces == Some(6));
+    assert!(stats.surface_area == Some(24.0));
+    assert!(stats.face_freq_table == Some(vec![0, 0, 0, 0, 6]));
+    assert!(stats.face_orders == Some(vec![4; 6]));
+    assert!(stats.face_areas == Some(vec![4.0; 6]));
+    assert!(stats.face_vertices == Some(cube.face_vertices()));
+    assert!(stats.normals == Some(cube.normals()));
+    assert!(stats.neighbors.is_none());
+    assert!(stats.volume == Some(8.0));
+
+    let cube = VoronoiCellNeighbor::init(&[-1.0; 3], &[1.0; 3]);
+    let stats = CellStats::builder()
+        .neighbors()
+        .compute(&cube, 0, &[0.0; 3], 0.5);
+    assert!(stats.neighbors == Some(cube.neighbors()));
+
+    let format = CustomFormat::new("%i %P %C %n").unwrap();
+    let builder = CellStatsBuilder::from_format(&format);
+    assert!(builder == CellStats::builder().vertices().centroid().neighbors());
+    assert!(builder.uses_neighbors());
+    assert!(
+        !CellStatsBuilder::from_format(&CustomFormat::new("%i %q %r").unwrap()).uses_neighbors()
+    );
+    assert!(
+        CellStatsBuilder::from_format(&CustomFormat::new("%i %q %r").unwrap())
+            == CellStatsBuilder::new()
+    );
+
+    let mut con = Container::new(&[0.0; 3], &[2.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.5, 1.0, 1.0]).unwrap();
+    con.put(8, &[1.5, 1.0, 1.0]).unwrap();
+    let all = builder.volume().compute_container(&con).unwrap();
+    assert!(all.len() == 2);
+    for stats in &all {
+        assert!(stats.volume == Some(4.0));
+        let neighbors = stats.neighbors.as_ref().unwrap();
+        assert!(neighbors.contains(&if stats.id == 7 { 8 } else { 7 }));
+    }
+    let all = CellStats::builder()
+        .volume()
+        .compute_container(&con)
+        .unwrap();
+    assert!(all
+        .iter()
+        .all(|s| s.neighbors.is_none() && s.volume == Some(4.0)));
+}
@@ -0,0 +1,468 @@
+use crate::rust::container_base::{ContainerBase, ContainerBaseFFI, ContainerParallelFFI, Drawing};
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use crate::rust::wall::{Wall, Walls};
+use cpp::cpp;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+}}
+
+/// Checks the geometry of a rectangular container: its bounds, its grid of
+/// computational blocks, and its initial memory allocation.
+pub(crate) fn check_geometry(
+    xyz_min: &[f64; 3],
+    xyz_max: &[f64; 3],
+    grid: &[i32; 3],
+    init_mem: i32,
+) -> Result<(), Error> {
+    let reason = if !xyz_min.iter().chain(xyz_max).all(|x| x.is_finite()) {
+        "bounds must be finite"
+    } else if !(0..3).all(|i| xyz_min[i] < xyz_max[i]) {
+        "minimum coordinates must be less than the maximum coordinates"
+    } else if !grid.iter().all(|&n| n > 0) {
+        "grid must have at least one block in each direction"
+    } else if init_mem <= 0 {
+        "initial memory must be positive"
+    } else {
+        return Ok(());
+    };
+    Err(Error::InvalidContainer(reason.to_string()))
+}
+
+/// A system of particles in a three-dimensional rectangular box, for computing
+/// the regular Voronoi tessellation.
+///
+/// The container divides the box into a rectangular grid of computational
+/// blocks, and sorts each particle into one of these blocks as it is inserted.
+/// This grid is used to quickly locate the particles which are near to one
+/// another, so that the Voronoi cell of each particle can be computed by
+/// cutting it with the planes of only its closest neighbors. Any combination
+/// of non-periodic and periodic coordinates can be used in the three
+/// coordinate directions.
+pub struct Container {
+    ptr: *mut std::ffi::c_void,
+    walls: Walls,
+}
+
+impl Container {
+    /// Sets up the geometry of the container.
+    ///
+    /// __Parameters:__
+    /// * `xyz_min` The minimum coordinates.
+    /// * `xyz_max` The maximum coordinates.
+    /// * `grid` The number of computational blocks in each of the three
+    ///   coordinate directions.
+    /// * `periodic` Flags setting whether the container is periodic in each
+    ///   coordinate direction.
+    /// * `init_mem` The initial memory allocation for each block, as a number
+    ///   of particles. More memory is allocated as required.
+    ///
+    /// __Returns:__
+    ///     An `InvalidContainer` error if the bounds are not finite, or if the
+    ///     minimum coordinates are not less than the maximum coordinates, or
+    ///     if the grid or the initial memory is not positive. Also returns an
+    ///     error if the memory for the container could not be allocated.
+    pub fn new(
+        xyz_min: &[f64; 3],
+        xyz_max: &[f64; 3],
+        grid: &[i32; 3],
+        periodic: &[bool; 3],
+        init_mem: i32,
+    ) -> Result<Self, Error> {
+        check_geometry(xyz_min, xyz_max, grid, init_mem)?;
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [xyz_min as "double*", xyz_max as "double*",
+                grid as "int*", periodic as "bool*", init_mem as "int", e as "void*"]
+                -> *mut std::ffi::c_void as "container*" {
+            container *con = nullptr;
+            voroxx_try(e, [&] {
+                con = new container(
+                    xyz_min[0], xyz_max[0], xyz_min[1], xyz_max[1], xyz_min[2], xyz_max[2],
+                    grid[0], grid[1], grid[2],
+                    periodic[0], periodic[1], periodic[2],
+                    init_mem);
+            });
+            return con;
+        });
+        Error::check(
+            Self {
+                ptr,
+                walls: Walls::new(),
+            },
+            error,
+        )
+    }
+
+    /// Puts a particle into the correct block of the container. Particles
+    /// which are outside of the container in a non-periodic coordinate are
+    /// not stored, and return the error `OutOfBounds`. Returns an error if the
+    /// memory limit was exceeded.
+    ///
+    /// __Parameters:__
+    /// * `id` The numerical ID of the inserted particle.
+    /// * `xyz` The position vector of the inserted particle.
+    pub fn put(&mut self, id: i32, xyz: &[f64; 3]) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let mut error = None;
+        let e = &mut error;
+        let inside = cpp!(unsafe [ptr as "container*", id as "int", xyz as "double*",
+                e as "void*"] -> bool as "bool" {
+            if(!voroxx_put_inside(*ptr, xyz[0], xyz[1], xyz[2])) return false;
+            voroxx_try(e, [&] {
+                ptr->put(id, xyz[0], xyz[1], xyz[2]);
+            });
+            return true;
+        });
+        if !inside {
+            return Err(Error::OutOfBounds);
+        }
+        Error::check((), error)
+    }
+
+    /// Adds a wall to the container. Every Voronoi cell which the container
+    /// computes is cut by all of its walls. The container takes ownership of
+    /// the wall, and keeps it for the rest of its lifetime. The wall can be
+    /// one of the built-in wall types, or any other type which implements the
+    /// trait `Wall`. Returns an error if the memory limit for walls was
+    /// exceeded.
+    pub fn add_wall<W: Wall + 'static>(&mut self, wall: W) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let wall_ptr = self.walls.push(Box::new(wall));
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container*", wall_ptr as "wall*", e as "void*"] {
+            voroxx_try(e, [&] {
+                ptr->add_wall(wall_ptr);
+            });
+        });
+        Error::check((), error)
+    }
+
+    /// Tests to see whether a point is inside all of the walls which have been
+    /// added to the container. The boundaries of the container are not tested.
+    pub fn point_inside_walls(&self, xyz: &[f64; 3]) -> bool {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*", xyz as "double*"] -> bool as "bool" {
+            return ptr->point_inside_walls(xyz[0], xyz[1], xyz[2]);
+        })
+    }
+}
+
+impl ContainerBaseFFI for Container {
+    fn ptr(&self) -> *mut std::ffi::c_void {
+        self.ptr
+    }
+
+    fn new_loop(&self) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*"] -> *mut std::ffi::c_void as "c_loop_all*" {
+            return new c_loop_all(*ptr);
+        })
+    }
+
+    fn delete_loop(&self, vl: *mut std::ffi::c_void) {
+        cpp!(unsafe [vl as "c_loop_all*"] {
+            delete vl;
+        })
+    }
+
+    fn compute_next(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*", vl as "c_loop_all*", started as "bool*",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell*" {
+            return voroxx_compute_next<voronoicell>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn compute_next_neighbor(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*", vl as "c_loop_all*", started as "bool*",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            return voroxx_compute_next<voronoicell_neighbor>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn draw(&self, drawing: Drawing, fp: *mut std::ffi::c_void) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let drawing = drawing as i32;
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container*", drawing as "int", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { voroxx_draw(*ptr, drawing, fp); });
+        });
+        Error::check((), error)
+    }
+
+    fn print_custom_file(
+        &self,
+        format: &CustomFormat,
+        fp: *mut std::ffi::c_void,
+    ) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let format = format.as_c_str().as_ptr();
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container*", format as "const char*", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { ptr->print_custom(format, fp); });
+        });
+        Error::check((), error)
+    }
+}
+
+impl ContainerParallelFFI for Container {
+    fn number_of_blocks(&self) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*"] -> i32 as "int" {
+            return ptr->nxyz;
+        })
+    }
+
+    fn block_size(&self, ijk: i32) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*", ijk as "int"] -> i32 as "int" {
+            return ptr->co[ijk];
+        })
+    }
+
+    fn new_thread_compute(&self) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*"]
+                -> *mut std::ffi::c_void as "voroxx_thread_compute<container>*" {
+            return new voroxx_thread_compute<container>(*ptr);
+        })
+    }
+
+    fn delete_thread_compute(&self, tc: *mut std::ffi::c_void) {
+        cpp!(unsafe [tc as "voroxx_thread_compute<container>*"] {
+            delete tc;
+        })
+    }
+
+    fn compute_particle(
+        &self,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        cpp!(unsafe [tc as "voroxx_thread_compute<container>*", ijk as "int", q as "int",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell*" {
+            return voroxx_compute_particle<voronoicell>(*tc, ijk, q, *id, xyz, *r, error);
+        })
+    }
+
+    fn compute_particle_neighbor(
+        &self,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        cpp!(unsafe [tc as "voroxx_thread_compute<container>*", ijk as "int", q as "int",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            return voroxx_compute_particle<voronoicell_neighbor>(*tc, ijk, q, *id, xyz, *r, error);
+        })
+    }
+}
+
+impl ContainerBase for Container {
+    fn total_particles(&self) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*"] -> i32 as "int" {
+            return ptr->total_particles();
+        })
+    }
+
+    fn clear(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*"] {
+            ptr->clear();
+        });
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::VoronoiCellBase;
+    let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[2, 2, 2], &[false; 3], 8).unwrap();
+    assert!(con.total_particles() == 0);
+    assert!(con.cells().next().is_none());
+    for i in 0..8 {
+        let x = (i & 1) as f64 * 0.5 + 0.25;
+        let y = ((i >> 1) & 1) as f64 * 0.5 + 0.25;
+        let z = ((i >> 2) & 1) as f64 * 0.5 + 0.25;
+        con.put(i, &[x, y, z]).unwrap();
+    }
+    // Outside of the container, this particle is not stored.
+    assert!(con.put(8, &[2.0, 0.5, 0.5]) == Err(crate::Error::OutOfBounds));
+    assert!(con.put(8, &[0.5, -0.1, 0.5]) == Err(crate::Error::OutOfBounds));
+    assert!(con.total_particles() == 8);
+    let mut ids = vec![];
+    let mut total_volume = 0.0;
+    for (id, xyz, r, cell) in con.cells().map(Result::unwrap) {
+        ids.push(id);
+        assert!(r == 0.5);
+        assert!(xyz.iter().all(|&c| c == 0.25 || c == 0.75));
+        assert!((cell.volume() - 0.125).abs() < 1e-12);
+        assert!(cell.number_of_faces() == 6);
+        total_volume += cell.volume();
+    }
+    ids.sort();
+    assert!(ids == (0..8).collect::<Vec<_>>());
+    assert!((total_volume - 1.0).abs() < 1e-12);
+    for (id, _, _, cell) in con.cells_neighbor().map(Result::unwrap) {
+        let n = cell.neighbors();
+        assert!(n.len() == 6);
+        assert!(n.iter().filter(|&&n| n < 0).count() == 3);
+        assert!(n.iter().all(|&n| (-6..8).contains(&n) && n != id));
+    }
+    // Dropping an iterator part of the way through.
+    assert!(con.cells().take(3).count() == 3);
+    con.clear();
+    assert!(con.total_particles() == 0);
+    assert!(con.cells().count() == 0);
+
+    let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[true; 3], 8).unwrap();
+    con.put(0, &[0.5, 0.5, 0.5]).unwrap();
+    let (_, _, _, cell) = con.cells().next().unwrap().unwrap();
+    assert!((cell.volume() - 1.0).abs() < 1e-12);
+}
+
+/// Check that walls cut the cells, and are reported as neighbors.
+#[test]
+fn walls() {
+    use crate::{VoronoiCellBase, WallCylinder, WallPlane};
+    let mut con = Container::new(&[-1.0; 3], &[1.0; 3], &[2, 2, 2], &[false; 3], 8).unwrap();
+    con.add_wall(WallCylinder::new(&[0.0; 3], &[0.0, 0.0, 1.0], 0.8, -7))
+        .unwrap();
+    con.add_wall(WallPlane::new(&[0.0, 0.0, 1.0], 0.5, -8))
+        .unwrap();
+    assert!(con.point_inside_walls(&[0.0, 0.0, 0.0]));
+    assert!(!con.point_inside_walls(&[0.7, 0.7, 0.0]));
+    assert!(!con.point_inside_walls(&[0.0, 0.0, 0.7]));
+    con.put(0, &[0.5, 0.0, 0.0]).unwrap();
+    con.put(1, &[-0.5, 0.0, 0.0]).unwrap();
+    for (_, _, _, cell) in con.cells_neighbor().map(Result::unwrap) {
+        let neighbors = cell.neighbors();
+        assert!(neighbors.contains(&-7));
+        assert!(neighbors.contains(&-8));
+        assert!(neighbors.contains(&-5));
+        assert!(cell.volume() < 2.0 * 1.0 * 1.5);
+    }
+}
+
+/// Write a container in the POV-Ray and gnuplot formats.
+#[test]
+fn draw() {
+    let mut con = Container::new(&[0.0; 3], &[2.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.5, 1.0, 1.0]).unwrap();
+    con.put(8, &[1.5, 1.0, 1.0]).unwrap();
+    let draw = |f: &dyn Fn(&mut dyn std::io::Write) -> std::io::Result<()>| {
+        let mut buffer = vec![];
+        f(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    };
+    assert!(draw(&|w| con.draw_particles(w)) == "7 0.5 1 1\n8 1.5 1 1\n");
+    assert!(draw(&|w| con.draw_particles_pov(w)).contains("// id 7\nsphere{<0.5,1,1>,s}\n"));
+    let cells = draw(&|w| con.draw_cells_pov(w));
+    assert!(cells.contains("// cell 7\n") && cells.contains("// cell 8\n"));
+    assert!(cells.matches("sphere{").count() == 16);
+    assert!(draw(&|w| con.draw_cells_gnuplot(w)).contains("2 2 2\n"));
+    assert!(draw(&|w| con.draw_domain_gnuplot(w)).contains("2 2 2"));
+    assert!(draw(&|w| con.draw_domain_pov(w)).contains("cylinder{"));
+
+    // Errors from computing the cells are returned, and nothing is written.
+    struct Broken;
+    impl Wall for Broken {
+        fn point_inside(&self, _xyz: &[f64; 3]) -> bool {
+            true
+        }
+        fn cut_cell(
+            &self,
+            _cell: &mut dyn crate::VoronoiCellBase,
+            _xyz: &[f64; 3],
+        ) -> Result<bool, Error> {
+            Err(Error::Internal("broken wall".to_string()))
+        }
+    }
+    con.add_wall(Broken).unwrap();
+    let mut buffer = vec![];
+    let error = con.draw_cells_pov(&mut buffer).unwrap_err();
+    assert!(error.to_string() == "internal error: broken wall");
+    assert!(buffer.is_empty());
+    con.draw_particles(&mut buffer).unwrap();
+    assert!(!buffer.is_empty());
+}
+
+/// Print a container with the custom output format of Voro++.
+#[test]
+fn print_custom() {
+    use crate::CustomFormat;
+    let mut con = Container::new(&[0.0; 3], &[2.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.5, 1.0, 1.0]).unwrap();
+    con.put(8, &[1.5, 1.0, 1.0]).unwrap();
+    let mut buffer = vec![];
+    let format = CustomFormat::new("%i %v %n").unwrap();
+    con.print_custom(&format, &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.len() == 2);
+    assert!(lines[0].starts_with("7 4 "));
+    assert!(lines[0].split(' ').skip(2).any(|n| n == "8"));
+    assert!(lines[1].starts_with("8 4 "));
+}
+
+#[test]
+fn invalid_geometry() {
+    let new = |min: f64, grid: i32, init_mem: i32| {
+        Container::new(&[min; 3], &[1.0; 3], &[grid; 3], &[false; 3], init_mem)
+    };
+    assert!(new(0.0, 1, 8).is_ok());
+    match new(1.0, 1, 8) {
+        Err(Error::InvalidContainer(msg)) => assert!(msg.contains("less than")),
+        _ => panic!(),
+    }
+    assert!(new(f64::NAN, 1, 8).is_err());
+    assert!(new(f64::NEG_INFINITY, 1, 8).is_err());
+    assert!(new(0.0, 0, 8).is_err());
+    assert!(new(0.0, 1, 0).is_err());
+}
@@ -0,0 +1,441 @@
+use crate::rust::c_file::write_via_c_file;
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell::VoronoiCell;
+use crate::rust::voronoi_cell_base::VoronoiCellBase;
+use crate::rust::voronoi_cell_neighbor::VoronoiCellNeighbor;
+use cpp::cpp;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+
+    // Tests whether container_base::put would store a particle, using the same
+    // test as container_base::put_remap.
+    inline bool voroxx_put_inside(container_base &con, double x, double y, double z) {
+        auto inside = [](double x, double a, double sp, int n, bool periodic) {
+            if(periodic) return true;
+            double i = (x - a) * sp;
+            int ijk = i < 0 ? int(i) - 1 : int(i);
+            return ijk >= 0 && ijk < n;
+        };
+        return inside(x, con.ax, con.xsp, con.nx, con.xperiodic)
+            && inside(y, con.ay, con.ysp, con.ny, con.yperiodic)
+            && inside(z, con.az, con.zsp, con.nz, con.zperiodic);
+    }
+
+    // Runs one of the output routines of a container, which are numbered the
+    // same as the Rust enum "Drawing".
+    template<class c_class>
+    void voroxx_draw(c_class &con, int drawing, FILE *fp) {
+        switch(drawing) {
+            case 0: con.draw_particles(fp); break;
+            case 1: con.draw_particles_pov(fp); break;
+            case 2: con.draw_cells_gnuplot(fp); break;
+            case 3: con.draw_cells_pov(fp); break;
+            case 4: con.draw_domain_gnuplot(fp); break;
+            case 5: con.draw_domain_pov(fp); break;
+        }
+    }
+}}
+
+cpp! {{
+    // The scratch space for computing Voronoi cells on one thread. Computing a
+    // cell modifies the radius information which is stored in the container,
+    // so each thread uses its own shallow copy of the container, which shares
+    // the particles and walls of the original. The copy is freed without
+    // running its destructor, which would free the shared memory.
+    template<class c_class>
+    struct voroxx_thread_compute {
+        c_class *con;
+        voro_compute<c_class> vc;
+        voroxx_thread_compute(c_class &con_) : con(new c_class(con_)),
+            vc(*con, con->xperiodic ? 2 * con->nx + 1 : con->nx,
+                     con->yperiodic ? 2 * con->ny + 1 : con->ny,
+                     con->zperiodic ? 2 * con->nz + 1 : con->nz) {}
+        ~voroxx_thread_compute() {
+            ::operator delete(con);
+        }
+    };
+
+    // Computes the Voronoi cell of particle "q" in block "ijk". Returns a newly
+    // allocated cell, or a null pointer if the cell could not be computed or
+    // an error is stored into "error".
+    template<class v_cell, class c_class>
+    v_cell* voroxx_compute_particle(voroxx_thread_compute<c_class> &tc, int ijk, int q,
+            int &pid, double *xyz, double &r, void *error) {
+        c_class &con = *tc.con;
+        int k = ijk / con.nxy, ijkt = ijk - con.nxy * k, j = ijkt / con.nx, i = ijkt - j * con.nx;
+        v_cell* c = nullptr;
+        bool found = false;
+        voroxx_try(error, [&] {
+            c = new v_cell(con);
+            if(tc.vc.compute_cell(*c, ijk, q, i, j, k)) {
+                double *pp = con.p[ijk] + con.ps * q;
+                pid = con.id[ijk][q];
+                xyz[0] = pp[0];
+                xyz[1] = pp[1];
+                xyz[2] = pp[2];
+                r = con.ps == 3 ? default_radius : pp[3];
+                found = true;
+            }
+        });
+        if(found) return c;
+        delete c;
+        return nullptr;
+    }
+}}
+
+cpp! {{
+    // Advances a loop to the next particle whose Voronoi cell can be computed,
+    // and computes it. Returns a newly allocated cell, or a null pointer when
+    // the loop is exhausted or an error is stored into "error".
+    template<class v_cell, class c_class, class c_loop>
+    v_cell* voroxx_compute_next(c_class &con, c_loop &vl, bool &started,
+            int &pid, double *xyz, double &r, void *error) {
+        v_cell* c = nullptr;
+        bool found = false;
+        voroxx_try(error, [&] {
+            bool more = started ? vl.inc() : vl.start();
+            started = true;
+            if(!more) return;
+            c = new v_cell(con);
+            do {
+                if(con.compute_cell(*c, vl)) {
+                    vl.pos(pid, xyz[0], xyz[1], xyz[2], r);
+                    found = true;
+                    return;
+                }
+            } while(vl.inc());
+        });
+        if(found) return c;
+        delete c;
+        return nullptr;
+    }
+}}
+
+/// Private trait. Allows the public trait "ContainerBase" to access the
+/// underlying C++ structures.
+pub trait ContainerBaseFFI {
+    fn ptr(&self) -> *mut std::ffi::c_void;
+
+    /// Makes a new loop over all of the particles in the container.
+    fn new_loop(&self) -> *mut std::ffi::c_void;
+
+    /// Frees a loop which was made by method `new_loop`.
+    fn delete_loop(&self, vl: *mut std::ffi::c_void);
+
+    /// Advances the loop to the next particle and computes its Voronoi cell,
+    /// returning a pointer to a new voronoicell or null if the loop is done.
+    /// If the computation fails then it returns null and sets `error`.
+    fn compute_next(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void;
+
+    /// Same as method `compute_next` except that it returns a pointer to a
+    /// new voronoicell_neighbor.
+    fn compute_next_neighbor(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void;
+
+    /// Runs one of the output routines of the container, writing to the C
+    /// file handle `fp`.
+    fn draw(&self, drawing: Drawing, fp: *mut std::ffi::c_void) -> Result<(), Error>;
+
+    /// Computes the Voronoi cell of every particle, and writes information
+    /// about them in a custom format to the C file handle `fp`.
+    fn print_custom_file(
+        &self,
+        format: &CustomFormat,
+        fp: *mut std::ffi::c_void,
+    ) -> Result<(), Error>;
+}
+
+/// The output routines of the containers, see trait `ContainerBase`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Drawing {
+    Particles = 0,
+    ParticlesPov = 1,
+    CellsGnuplot = 2,
+    CellsPov = 3,
+    DomainGnuplot = 4,
+    DomainPov = 5,
+}
+
+/// Runs one of the output routines of a container, and copies its output into
+/// a Rust writer.
+fn draw<T: ContainerBaseFFI + ?Sized>(
+    container: &T,
+    drawing: Drawing,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    write_via_c_file(w, |fp| {
+        container
+            .draw(drawing, fp)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+    })
+}
+
+/// Private trait. Allows the containers to compute the Voronoi cells of
+/// their particles on many threads at once. Each thread needs its own scratch
+/// space, which is made by method `new_thread_compute`.
+#[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+pub trait ContainerParallelFFI {
+    /// Returns the total number of computational blocks in the container.
+    fn number_of_blocks(&self) -> i32;
+
+    /// Returns the number of particles which are stored in a block.
+    fn block_size(&self, ijk: i32) -> i32;
+
+    /// Makes new scratch space for computing cells on one thread.
+    fn new_thread_compute(&self) -> *mut std::ffi::c_void;
+
+    /// Frees the scratch space which was made by `new_thread_compute`.
+    fn delete_thread_compute(&self, tc: *mut std::ffi::c_void);
+
+    /// Computes the Voronoi cell of particle `q` in block `ijk`, returning a
+    /// pointer to a new voronoicell, or null if the cell could not be
+    /// computed. If the computation fails then it returns null and sets
+    /// `error`.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_particle(
+        &self,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void;
+
+    /// Same as method `compute_particle` except that it returns a pointer to
+    /// a new voronoicell_neighbor.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_particle_neighbor(
+        &self,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void;
+}
+
+/// Private trait. Selects which variant of Voronoi cell is computed while
+/// iterating over a container.
+pub trait ContainerCell: VoronoiCellBase + Sized {
+    fn compute_next<T: ContainerBaseFFI>(
+        container: &T,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+    ) -> Result<Option<Self>, Error>;
+}
+
+impl ContainerCell for VoronoiCell {
+    fn compute_next<T: ContainerBaseFFI>(
+        container: &T,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+    ) -> Result<Option<Self>, Error> {
+        let mut error = None;
+        let cell = container.compute_next(vl, started, id, xyz, r, &mut error);
+        if cell.is_null() {
+            Error::check(None, error)
+        } else {
+            Ok(Some(VoronoiCell(cell)))
+        }
+    }
+}
+
+impl ContainerCell for VoronoiCellNeighbor {
+    fn compute_next<T: ContainerBaseFFI>(
+        container: &T,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+    ) -> Result<Option<Self>, Error> {
+        let mut error = None;
+        let cell = container.compute_next_neighbor(vl, started, id, xyz, r, &mut error);
+        if cell.is_null() {
+            Error::check(None, error)
+        } else {
+            Ok(Some(VoronoiCellNeighbor(cell)))
+        }
+    }
+}
+
+/// Methods which are common to all variants of containers.
+pub trait ContainerBase: ContainerBaseFFI {
+    /// Sums up the total number of stored particles.
+    fn total_particles(&self) -> i32;
+
+    /// Removes all of the particles from the container.
+    fn clear(&mut self);
+
+    /// Computes the Voronoi cell of every particle in the container.
+    ///
+    /// Returns an iterator over the tuples (`id`, `position`, `radius`,
+    /// `cell`) of each particle, in the order in which they are stored in the
+    /// computational blocks. If the computation of a cell fails then the
+    /// iterator returns the error, and then stops. Containers without radius
+    /// information report the default radius of 0.5 for every particle. The
+    /// vertices of each cell are in the local coordinate system, relative to
+    /// the particle position.
+    /// Particles whose cell could not be computed, because it was entirely
+    /// removed by a wall or boundary condition, are skipped.
+    fn cells(&self) -> Cells<'_, Self, VoronoiCell>
+    where
+        Self: Sized,
+    {
+        Cells {
+            container: self,
+            vl: self.new_loop(),
+            started: false,
+            done: false,
+            cell_type: PhantomData,
+        }
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, with
+    /// neighbor information. The neighbor of each face is the ID of the
+    /// particle which made it. Faces made by the boundaries of a non-periodic
+    /// container have negative IDs from -1 to -6. In periodic coordinates the
+    /// neighbor may be a periodic image of a particle, which is reported with
+    /// the ID of the original particle.
+    ///
+    /// Returns an iterator over the tuples (`id`, `position`, `radius`,
+    /// `cell`) of each particle, the same as method `cells`.
+    fn cells_neighbor(&self) -> Cells<'_, Self, VoronoiCellNeighbor>
+    where
+        Self: Sized,
+    {
+        Cells {
+            container: self,
+            vl: self.new_loop(),
+            started: false,
+            done: false,
+            cell_type: PhantomData,
+        }
+    }
+
+    /// Writes the ID and position of every particle, one particle per line,
+    /// which can be plotted by gnuplot.
+    fn draw_particles(&self, w: &mut dyn Write) -> io::Result<()> {
+        draw(self, Drawing::Particles, w)
+    }
+
+    /// Writes the position of every particle in the POV-Ray format, as a
+    /// sphere whose radius is the POV-Ray variable `s`.
+    fn draw_particles_pov(&self, w: &mut dyn Write) -> io::Result<()> {
+        draw(self, Drawing::ParticlesPov, w)
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// writes their edges in the gnuplot format. See the method
+    /// `VoronoiCellBase::draw_gnuplot`. Returns an error if the computation
+    /// of a cell failed, in which case nothing is written.
+    fn draw_cells_gnuplot(&self, w: &mut dyn Write) -> io::Result<()> {
+        draw(self, Drawing::CellsGnuplot, w)
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// writes them in the POV-Ray format. See the method
+    /// `VoronoiCellBase::draw_pov`. Returns an error if the computation of a
+    /// cell failed, in which case nothing is written.
+    fn draw_cells_pov(&self, w: &mut dyn Write) -> io::Result<()> {
+        draw(self, Drawing::CellsPov, w)
+    }
+
+    /// Writes an outline of the domain of the container in the gnuplot
+    /// format.
+    fn draw_domain_gnuplot(&self, w: &mut dyn Write) -> io::Result<()> {
+        draw(self, Drawing::DomainGnuplot, w)
+    }
+
+    /// Writes an outline of the domain of the container in the POV-Ray
+    /// format.
+    fn draw_domain_pov(&self, w: &mut dyn Write) -> io::Result<()> {
+        draw(self, Drawing::DomainPov, w)
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// writes information about each of them on its own line, in a custom
+    /// format. See the method `VoronoiCellBase::output_custom`. Returns an
+    /// error if the computation of a cell failed, in which case nothing is
+    /// written.
+    fn print_custom(&self, format: &CustomFormat, w: &mut dyn Write) -> io::Result<()> {
+        write_via_c_file(w, |fp| {
+            self.print_custom_file(format, fp)
+                .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+        })
+    }
+}
+
+/// Iterator over the Voronoi cells of the particles in a container, see
+/// methods `ContainerBase::cells` and `ContainerBase::cells_neighbor`.
+pub struct Cells<'a, T: ContainerBase, C: ContainerCell> {
+    container: &'a T,
+    vl: *mut std::ffi::c_void,
+    started: bool,
+    done: bool,
+    cell_type: PhantomData<C>,
+}
+
+impl<'a, T: ContainerBase, C: ContainerCell> Iterator for Cells<'a, T, C> {
+    type Item = Result<(i32, [f64; 3], f64, C), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut id = 0;
+        let mut xyz = [f64::NAN; 3];
+        let mut r = f64::NAN;
+        let cell = C::compute_next(
+            self.container,
+            self.vl,
+            &mut self.started,
+            &mut id,
+            &mut xyz,
+            &mut r,
+        );
+        match cell {
+            Ok(cell) => cell.map(|c| Ok((id, xyz, r, c))),
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'a, T: ContainerBase, C: ContainerCell> Drop for Cells<'a, T, C> {
+    fn drop(&mut self) {
+        self.container.delete_loop(self.vl);
+    }
+}
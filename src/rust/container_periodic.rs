@@ -0,0 +1,269 @@
+use crate::rust::container_base::{ContainerBase, ContainerBaseFFI, Drawing};
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use cpp::cpp;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+}}
+
+/// Checks that the three periodicity vectors of a periodic container describe
+/// a valid parallelepiped, in the lower triangular form that Voro++ requires.
+pub(crate) fn check_lattice(lattice: &[[f64; 3]; 3]) -> Result<(), Error> {
+    let [a, b, c] = lattice;
+    let reason = if !lattice.iter().flatten().all(|x| x.is_finite()) {
+        "periodicity vectors must be finite"
+    } else if a[1] != 0.0 || a[2] != 0.0 || b[2] != 0.0 {
+        "periodicity vectors must have the form (bx,0,0), (bxy,by,0), (bxz,byz,bz)"
+    } else if !(a[0] > 0.0 && b[1] > 0.0 && c[2] > 0.0) {
+        "periodicity vectors must have positive bx, by, and bz"
+    } else {
+        return Ok(());
+    };
+    Err(Error::InvalidLattice(reason.to_string()))
+}
+
+/// A system of particles in a three-dimensional, fully periodic domain, for
+/// computing the regular Voronoi tessellation.
+///
+/// The domain is a parallelepiped defined by the three periodicity vectors
+/// (bx,0,0), (bxy,by,0), and (bxz,byz,bz), which allows for non-orthogonal
+/// (triclinic) domains such as sheared boxes. Internally, the container stores
+/// particles in the box 0<x<bx, 0<y<by, 0<z<bz, and constructs periodic images
+/// of particles when they are necessary for the computation.
+pub struct ContainerPeriodic {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl ContainerPeriodic {
+    /// Sets up the geometry of the container.
+    ///
+    /// __Parameters:__
+    /// * `lattice` The three periodicity vectors (bx,0,0), (bxy,by,0), and
+    ///   (bxz,byz,bz).
+    /// * `grid` The number of computational blocks in each of the three
+    ///   coordinate directions.
+    /// * `init_mem` The initial memory allocation for each block, as a number
+    ///   of particles. More memory is allocated as required.
+    ///
+    /// __Returns:__
+    ///     An `InvalidLattice` error if the periodicity vectors are not of the
+    ///     required form, or are not finite, or if `bx`, `by`, or `bz` is not
+    ///     positive. Also returns an error if the unit Voronoi cell of the
+    ///     lattice could not be computed.
+    pub fn new(lattice: &[[f64; 3]; 3], grid: &[i32; 3], init_mem: i32) -> Result<Self, Error> {
+        check_lattice(lattice)?;
+        assert!(grid.iter().all(|&n| n > 0));
+        assert!(init_mem > 0);
+        let b = [
+            lattice[0][0],
+            lattice[1][0],
+            lattice[1][1],
+            lattice[2][0],
+            lattice[2][1],
+            lattice[2][2],
+        ];
+        let b = &b;
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [b as "double*", grid as "int*", init_mem as "int", e as "void*"]
+                -> *mut std::ffi::c_void as "container_periodic*" {
+            container_periodic *con = nullptr;
+            voroxx_try(e, [&] {
+                con = new container_periodic(b[0], b[1], b[2], b[3], b[4], b[5],
+                    grid[0], grid[1], grid[2], init_mem);
+            });
+            return con;
+        });
+        Error::check(Self { ptr }, error)
+    }
+
+    /// Puts a particle into the correct block of the container. Particles
+    /// which are outside of the primary domain are remapped into it, by
+    /// subtracting a whole number of periodicity vectors.
+    ///
+    /// __Parameters:__
+    /// * `id` The numerical ID of the inserted particle.
+    /// * `xyz` The position vector of the inserted particle.
+    ///
+    /// __Returns:__
+    ///     The periodic image (ai, aj, ak) which the particle was in, with
+    ///     (0, 0, 0) corresponding to the primary domain. Returns an error if
+    ///     the particle is at the same position as another particle, or if
+    ///     the memory limit was exceeded.
+    pub fn put(&mut self, id: i32, xyz: &[f64; 3]) -> Result<[i32; 3], Error> {
+        let ptr = self.ptr;
+        let mut image = [0; 3];
+        let a = &mut image;
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_periodic*", id as "int", xyz as "double*",
+                a as "int*", e as "void*"] {
+            voroxx_try(e, [&] {
+                ptr->put(id, xyz[0], xyz[1], xyz[2], a[0], a[1], a[2]);
+            });
+        });
+        Error::check(image, error)
+    }
+}
+
+impl ContainerBaseFFI for ContainerPeriodic {
+    fn ptr(&self) -> *mut std::ffi::c_void {
+        self.ptr
+    }
+
+    fn new_loop(&self) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic*"]
+                -> *mut std::ffi::c_void as "c_loop_all_periodic*" {
+            return new c_loop_all_periodic(*ptr);
+        })
+    }
+
+    fn delete_loop(&self, vl: *mut std::ffi::c_void) {
+        cpp!(unsafe [vl as "c_loop_all_periodic*"] {
+            delete vl;
+        })
+    }
+
+    fn compute_next(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic*", vl as "c_loop_all_periodic*",
+                started as "bool*", id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell*" {
+            return voroxx_compute_next<voronoicell>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn compute_next_neighbor(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic*", vl as "c_loop_all_periodic*",
+                started as "bool*", id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            return voroxx_compute_next<voronoicell_neighbor>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn draw(&self, drawing: Drawing, fp: *mut std::ffi::c_void) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let drawing = drawing as i32;
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_periodic*", drawing as "int", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { voroxx_draw(*ptr, drawing, fp); });
+        });
+        Error::check((), error)
+    }
+
+    fn print_custom_file(
+        &self,
+        format: &CustomFormat,
+        fp: *mut std::ffi::c_void,
+    ) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let format = format.as_c_str().as_ptr();
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_periodic*", format as "const char*", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { ptr->print_custom(format, fp); });
+        });
+        Error::check((), error)
+    }
+}
+
+impl ContainerBase for ContainerPeriodic {
+    fn total_particles(&self) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic*"] -> i32 as "int" {
+            int tp = 0;
+            for(int k = ptr->ez; k < ptr->wz; k++)
+                for(int j = ptr->ey; j < ptr->wy; j++)
+                    for(int i = 0; i < ptr->nx; i++)
+                        tp += ptr->co[i + ptr->nx * (j + ptr->oy * k)];
+            return tp;
+        })
+    }
+
+    fn clear(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic*"] {
+            ptr->clear();
+        });
+    }
+}
+
+impl Drop for ContainerPeriodic {
+    fn drop(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::VoronoiCellBase;
+    // A single particle in a cubic domain is its own neighbor on every face.
+    let cube = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut con = ContainerPeriodic::new(&cube, &[1, 1, 1], 8).unwrap();
+    assert!(con.put(7, &[0.5, 0.5, 0.5]).unwrap() == [0, 0, 0]);
+    assert!(con.total_particles() == 1);
+    let (id, _, _, cell) = con.cells_neighbor().next().unwrap().unwrap();
+    assert!(id == 7);
+    assert!((cell.volume() - 1.0).abs() < 1e-12);
+    assert!(cell.neighbors() == vec![7; 6]);
+
+    // A sheared domain, with particles put in several periodic images.
+    let sheared = [[2.0, 0.0, 0.0], [0.7, 1.5, 0.0], [-0.4, 0.3, 1.2]];
+    let mut con = ContainerPeriodic::new(&sheared, &[3, 2, 2], 8).unwrap();
+    assert!(con.put(0, &[0.5, 0.5, 0.5]).unwrap() == [0, 0, 0]);
+    assert!(con.put(1, &[3.0, 1.0, 0.3]).unwrap() == [1, 0, 0]);
+    assert!(con.put(2, &[-0.5, 0.5, 0.5]).unwrap() == [-1, 0, 0]);
+    assert!(con.put(3, &[1.0, 1.0, 1.7]).unwrap() == [0, 0, 1]);
+    assert!(con.total_particles() == 4);
+    let mut volume = 0.0;
+    for (_, xyz, r, cell) in con.cells_neighbor().map(Result::unwrap) {
+        assert!(r == 0.5);
+        assert!(xyz[0] >= 0.0 && xyz[0] < 2.0);
+        assert!(xyz[1] >= 0.0 && xyz[1] < 1.5);
+        assert!(xyz[2] >= 0.0 && xyz[2] < 1.2);
+        assert!(cell.neighbors().iter().all(|&n| (0..4).contains(&n)));
+        volume += cell.volume();
+    }
+    assert!((volume - 2.0 * 1.5 * 1.2).abs() < 1e-9);
+    con.clear();
+    assert!(con.total_particles() == 0);
+    assert!(con.cells().count() == 0);
+}
+
+#[test]
+fn invalid_lattice() {
+    let lattice = [[1.0, 0.5, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    match ContainerPeriodic::new(&lattice, &[1, 1, 1], 8) {
+        Err(Error::InvalidLattice(msg)) => assert!(msg.contains("(bx,0,0)")),
+        _ => panic!(),
+    }
+    let lattice = [[1.0, 0.0, 0.0], [0.0, f64::NAN, 0.0], [0.0, 0.0, 1.0]];
+    assert!(ContainerPeriodic::new(&lattice, &[1, 1, 1], 8).is_err());
+    let lattice = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]];
+    assert!(ContainerPeriodic::new(&lattice, &[1, 1, 1], 8).is_err());
+}
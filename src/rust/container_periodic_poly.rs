@@ -0,0 +1,228 @@
+use crate::rust::container_base::{ContainerBase, ContainerBaseFFI, Drawing};
+use crate::rust::container_periodic::check_lattice;
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use cpp::cpp;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+}}
+
+/// A system of particles with radii in a three-dimensional, fully periodic
+/// domain, for computing the radical Voronoi tessellation.
+///
+/// The domain is a parallelepiped defined by the three periodicity vectors
+/// (bx,0,0), (bxy,by,0), and (bxz,byz,bz). The container is otherwise the same
+/// as the `ContainerPeriodic` class.
+pub struct ContainerPeriodicPoly {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl ContainerPeriodicPoly {
+    /// Sets up the geometry of the container.
+    ///
+    /// __Parameters:__
+    /// * `lattice` The three periodicity vectors (bx,0,0), (bxy,by,0), and
+    ///   (bxz,byz,bz).
+    /// * `grid` The number of computational blocks in each of the three
+    ///   coordinate directions.
+    /// * `init_mem` The initial memory allocation for each block, as a number
+    ///   of particles. More memory is allocated as required.
+    ///
+    /// __Returns:__
+    ///     An `InvalidLattice` error if the periodicity vectors are not of the
+    ///     required form, or are not finite, or if `bx`, `by`, or `bz` is not
+    ///     positive. Also returns an error if the unit Voronoi cell of the
+    ///     lattice could not be computed.
+    pub fn new(lattice: &[[f64; 3]; 3], grid: &[i32; 3], init_mem: i32) -> Result<Self, Error> {
+        check_lattice(lattice)?;
+        assert!(grid.iter().all(|&n| n > 0));
+        assert!(init_mem > 0);
+        let b = [
+            lattice[0][0],
+            lattice[1][0],
+            lattice[1][1],
+            lattice[2][0],
+            lattice[2][1],
+            lattice[2][2],
+        ];
+        let b = &b;
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [b as "double*", grid as "int*", init_mem as "int", e as "void*"]
+                -> *mut std::ffi::c_void as "container_periodic_poly*" {
+            container_periodic_poly *con = nullptr;
+            voroxx_try(e, [&] {
+                con = new container_periodic_poly(b[0], b[1], b[2], b[3], b[4], b[5],
+                    grid[0], grid[1], grid[2], init_mem);
+            });
+            return con;
+        });
+        Error::check(Self { ptr }, error)
+    }
+
+    /// Puts a particle into the correct block of the container. Particles
+    /// which are outside of the primary domain are remapped into it, by
+    /// subtracting a whole number of periodicity vectors.
+    ///
+    /// __Parameters:__
+    /// * `id` The numerical ID of the inserted particle.
+    /// * `xyz` The position vector of the inserted particle.
+    /// * `r` The radius of the particle.
+    ///
+    /// __Returns:__
+    ///     The periodic image (ai, aj, ak) which the particle was in, with
+    ///     (0, 0, 0) corresponding to the primary domain. Returns an error if
+    ///     the particle is at the same position as another particle, or if
+    ///     the memory limit was exceeded.
+    pub fn put(&mut self, id: i32, xyz: &[f64; 3], r: f64) -> Result<[i32; 3], Error> {
+        let ptr = self.ptr;
+        let mut image = [0; 3];
+        let a = &mut image;
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_periodic_poly*", id as "int", xyz as "double*", r as "double",
+                a as "int*", e as "void*"] {
+            voroxx_try(e, [&] {
+                ptr->put(id, xyz[0], xyz[1], xyz[2], r, a[0], a[1], a[2]);
+            });
+        });
+        Error::check(image, error)
+    }
+}
+
+impl ContainerBaseFFI for ContainerPeriodicPoly {
+    fn ptr(&self) -> *mut std::ffi::c_void {
+        self.ptr
+    }
+
+    fn new_loop(&self) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic_poly*"]
+                -> *mut std::ffi::c_void as "c_loop_all_periodic*" {
+            return new c_loop_all_periodic(*ptr);
+        })
+    }
+
+    fn delete_loop(&self, vl: *mut std::ffi::c_void) {
+        cpp!(unsafe [vl as "c_loop_all_periodic*"] {
+            delete vl;
+        })
+    }
+
+    fn compute_next(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic_poly*", vl as "c_loop_all_periodic*",
+                started as "bool*", id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell*" {
+            return voroxx_compute_next<voronoicell>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn compute_next_neighbor(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic_poly*", vl as "c_loop_all_periodic*",
+                started as "bool*", id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            return voroxx_compute_next<voronoicell_neighbor>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn draw(&self, drawing: Drawing, fp: *mut std::ffi::c_void) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let drawing = drawing as i32;
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_periodic_poly*", drawing as "int", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { voroxx_draw(*ptr, drawing, fp); });
+        });
+        Error::check((), error)
+    }
+
+    fn print_custom_file(
+        &self,
+        format: &CustomFormat,
+        fp: *mut std::ffi::c_void,
+    ) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let format = format.as_c_str().as_ptr();
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_periodic_poly*", format as "const char*", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { ptr->print_custom(format, fp); });
+        });
+        Error::check((), error)
+    }
+}
+
+impl ContainerBase for ContainerPeriodicPoly {
+    fn total_particles(&self) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic_poly*"] -> i32 as "int" {
+            int tp = 0;
+            for(int k = ptr->ez; k < ptr->wz; k++)
+                for(int j = ptr->ey; j < ptr->wy; j++)
+                    for(int i = 0; i < ptr->nx; i++)
+                        tp += ptr->co[i + ptr->nx * (j + ptr->oy * k)];
+            return tp;
+        })
+    }
+
+    fn clear(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic_poly*"] {
+            ptr->clear();
+        });
+    }
+}
+
+impl Drop for ContainerPeriodicPoly {
+    fn drop(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_periodic_poly*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::VoronoiCellBase;
+    let sheared = [[2.0, 0.0, 0.0], [0.7, 1.0, 0.0], [-0.4, 0.3, 1.0]];
+    let mut con = ContainerPeriodicPoly::new(&sheared, &[2, 1, 1], 8).unwrap();
+    assert!(con.put(0, &[0.5, 0.5, 0.5], 0.6).unwrap() == [0, 0, 0]);
+    assert!(con.put(1, &[-0.5, 0.5, 0.5], 0.4).unwrap() == [-1, 0, 0]);
+    assert!(con.total_particles() == 2);
+    // The larger particle has the larger cell.
+    let mut cells: Vec<_> = con.cells_neighbor().map(Result::unwrap).collect();
+    cells.sort_by_key(|(id, _, _, _)| *id);
+    let (id, xyz, r, cell_0) = &cells[0];
+    assert!(*id == 0 && *xyz == [0.5, 0.5, 0.5] && *r == 0.6);
+    let (id, xyz, r, cell_1) = &cells[1];
+    assert!(*id == 1 && *xyz == [1.5, 0.5, 0.5] && *r == 0.4);
+    assert!(cell_0.volume() > cell_1.volume());
+    assert!((cell_0.volume() + cell_1.volume() - 2.0).abs() < 1e-9);
+    assert!(cell_0.neighbors().contains(&1));
+    assert!(cell_1.neighbors().contains(&0));
+    con.clear();
+    assert!(con.total_particles() == 0);
+    assert!(con.cells().count() == 0);
+}
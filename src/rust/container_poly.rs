@@ -0,0 +1,366 @@
+use crate::rust::container::check_geometry;
+use crate::rust::container_base::{ContainerBase, ContainerBaseFFI, ContainerParallelFFI, Drawing};
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use crate::rust::wall::{Wall, Walls};
+use cpp::cpp;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+}}
+
+/// A system of particles with radii in a three-dimensional rectangular box,
+/// for computing the radical Voronoi tessellation.
+///
+/// The radical Voronoi tessellation, also known as the Laguerre tessellation
+/// or power diagram, is a generalization of the Voronoi tessellation in which
+/// the faces between particles are shifted according to the particle radii,
+/// so that larger particles have larger cells. The container is otherwise the
+/// same as the `Container` class.
+pub struct ContainerPoly {
+    ptr: *mut std::ffi::c_void,
+    walls: Walls,
+}
+
+impl ContainerPoly {
+    /// Sets up the geometry of the container.
+    ///
+    /// __Parameters:__
+    /// * `xyz_min` The minimum coordinates.
+    /// * `xyz_max` The maximum coordinates.
+    /// * `grid` The number of computational blocks in each of the three
+    ///   coordinate directions.
+    /// * `periodic` Flags setting whether the container is periodic in each
+    ///   coordinate direction.
+    /// * `init_mem` The initial memory allocation for each block, as a number
+    ///   of particles. More memory is allocated as required.
+    ///
+    /// __Returns:__
+    ///     An `InvalidContainer` error if the bounds are not finite, or if the
+    ///     minimum coordinates are not less than the maximum coordinates, or
+    ///     if the grid or the initial memory is not positive. Also returns an
+    ///     error if the memory for the container could not be allocated.
+    pub fn new(
+        xyz_min: &[f64; 3],
+        xyz_max: &[f64; 3],
+        grid: &[i32; 3],
+        periodic: &[bool; 3],
+        init_mem: i32,
+    ) -> Result<Self, Error> {
+        check_geometry(xyz_min, xyz_max, grid, init_mem)?;
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [xyz_min as "double*", xyz_max as "double*",
+                grid as "int*", periodic as "bool*", init_mem as "int", e as "void*"]
+                -> *mut std::ffi::c_void as "container_poly*" {
+            container_poly *con = nullptr;
+            voroxx_try(e, [&] {
+                con = new container_poly(
+                    xyz_min[0], xyz_max[0], xyz_min[1], xyz_max[1], xyz_min[2], xyz_max[2],
+                    grid[0], grid[1], grid[2],
+                    periodic[0], periodic[1], periodic[2],
+                    init_mem);
+            });
+            return con;
+        });
+        Error::check(
+            Self {
+                ptr,
+                walls: Walls::new(),
+            },
+            error,
+        )
+    }
+
+    /// Puts a particle into the correct block of the container. Particles
+    /// which are outside of the container in a non-periodic coordinate are
+    /// not stored, and return the error `OutOfBounds`. Returns an error if the
+    /// memory limit was exceeded.
+    ///
+    /// __Parameters:__
+    /// * `id` The numerical ID of the inserted particle.
+    /// * `xyz` The position vector of the inserted particle.
+    /// * `r` The radius of the particle.
+    pub fn put(&mut self, id: i32, xyz: &[f64; 3], r: f64) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let mut error = None;
+        let e = &mut error;
+        let inside = cpp!(unsafe [ptr as "container_poly*", id as "int", xyz as "double*", r as "double",
+                e as "void*"] -> bool as "bool" {
+            if(!voroxx_put_inside(*ptr, xyz[0], xyz[1], xyz[2])) return false;
+            voroxx_try(e, [&] {
+                ptr->put(id, xyz[0], xyz[1], xyz[2], r);
+            });
+            return true;
+        });
+        if !inside {
+            return Err(Error::OutOfBounds);
+        }
+        Error::check((), error)
+    }
+
+    /// Adds a wall to the container. Every Voronoi cell which the container
+    /// computes is cut by all of its walls. The container takes ownership of
+    /// the wall, and keeps it for the rest of its lifetime. The wall can be
+    /// one of the built-in wall types, or any other type which implements the
+    /// trait `Wall`. Returns an error if the memory limit for walls was
+    /// exceeded.
+    pub fn add_wall<W: Wall + 'static>(&mut self, wall: W) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let wall_ptr = self.walls.push(Box::new(wall));
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_poly*", wall_ptr as "wall*", e as "void*"] {
+            voroxx_try(e, [&] {
+                ptr->add_wall(wall_ptr);
+            });
+        });
+        Error::check((), error)
+    }
+
+    /// Tests to see whether a point is inside all of the walls which have been
+    /// added to the container. The boundaries of the container are not tested.
+    pub fn point_inside_walls(&self, xyz: &[f64; 3]) -> bool {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*", xyz as "double*"] -> bool as "bool" {
+            return ptr->point_inside_walls(xyz[0], xyz[1], xyz[2]);
+        })
+    }
+}
+
+impl ContainerBaseFFI for ContainerPoly {
+    fn ptr(&self) -> *mut std::ffi::c_void {
+        self.ptr
+    }
+
+    fn new_loop(&self) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*"] -> *mut std::ffi::c_void as "c_loop_all*" {
+            return new c_loop_all(*ptr);
+        })
+    }
+
+    fn delete_loop(&self, vl: *mut std::ffi::c_void) {
+        cpp!(unsafe [vl as "c_loop_all*"] {
+            delete vl;
+        })
+    }
+
+    fn compute_next(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*", vl as "c_loop_all*", started as "bool*",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell*" {
+            return voroxx_compute_next<voronoicell>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn compute_next_neighbor(
+        &self,
+        vl: *mut std::ffi::c_void,
+        started: &mut bool,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*", vl as "c_loop_all*", started as "bool*",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            return voroxx_compute_next<voronoicell_neighbor>(*ptr, *vl, *started, *id, xyz, *r, error);
+        })
+    }
+
+    fn draw(&self, drawing: Drawing, fp: *mut std::ffi::c_void) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let drawing = drawing as i32;
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_poly*", drawing as "int", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { voroxx_draw(*ptr, drawing, fp); });
+        });
+        Error::check((), error)
+    }
+
+    fn print_custom_file(
+        &self,
+        format: &CustomFormat,
+        fp: *mut std::ffi::c_void,
+    ) -> Result<(), Error> {
+        let ptr = self.ptr;
+        let format = format.as_c_str().as_ptr();
+        let mut error = None;
+        let e = &mut error;
+        cpp!(unsafe [ptr as "container_poly*", format as "const char*", fp as "FILE*", e as "void*"] {
+            voroxx_try(e, [&] { ptr->print_custom(format, fp); });
+        });
+        Error::check((), error)
+    }
+}
+
+impl ContainerParallelFFI for ContainerPoly {
+    fn number_of_blocks(&self) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*"] -> i32 as "int" {
+            return ptr->nxyz;
+        })
+    }
+
+    fn block_size(&self, ijk: i32) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*", ijk as "int"] -> i32 as "int" {
+            return ptr->co[ijk];
+        })
+    }
+
+    fn new_thread_compute(&self) -> *mut std::ffi::c_void {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*"]
+                -> *mut std::ffi::c_void as "voroxx_thread_compute<container_poly>*" {
+            return new voroxx_thread_compute<container_poly>(*ptr);
+        })
+    }
+
+    fn delete_thread_compute(&self, tc: *mut std::ffi::c_void) {
+        cpp!(unsafe [tc as "voroxx_thread_compute<container_poly>*"] {
+            delete tc;
+        })
+    }
+
+    fn compute_particle(
+        &self,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        cpp!(unsafe [tc as "voroxx_thread_compute<container_poly>*", ijk as "int", q as "int",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell*" {
+            return voroxx_compute_particle<voronoicell>(*tc, ijk, q, *id, xyz, *r, error);
+        })
+    }
+
+    fn compute_particle_neighbor(
+        &self,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+        error: &mut Option<Error>,
+    ) -> *mut std::ffi::c_void {
+        cpp!(unsafe [tc as "voroxx_thread_compute<container_poly>*", ijk as "int", q as "int",
+                id as "int*", xyz as "double*", r as "double*", error as "void*"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            return voroxx_compute_particle<voronoicell_neighbor>(*tc, ijk, q, *id, xyz, *r, error);
+        })
+    }
+}
+
+impl ContainerBase for ContainerPoly {
+    fn total_particles(&self) -> i32 {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*"] -> i32 as "int" {
+            return ptr->total_particles();
+        })
+    }
+
+    fn clear(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*"] {
+            ptr->clear();
+        });
+    }
+}
+
+impl Drop for ContainerPoly {
+    fn drop(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "container_poly*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::VoronoiCellBase;
+    let mut con =
+        ContainerPoly::new(&[0.0; 3], &[2.0, 1.0, 1.0], &[2, 1, 1], &[false; 3], 8).unwrap();
+    con.put(0, &[0.5, 0.5, 0.5], 0.6).unwrap();
+    con.put(1, &[1.5, 0.5, 0.5], 0.4).unwrap();
+    assert!(con.total_particles() == 2);
+    // The radical plane between the particles is at x = 1 + (0.6² - 0.4²) / 2.
+    let mut cells: Vec<_> = con.cells().map(Result::unwrap).collect();
+    cells.sort_by_key(|(id, _, _, _)| *id);
+    assert!(cells.len() == 2);
+    let (id, xyz, r, cell) = &cells[0];
+    assert!(*id == 0 && *xyz == [0.5, 0.5, 0.5] && *r == 0.6);
+    assert!((cell.volume() - 1.1).abs() < 1e-12);
+    let (id, xyz, r, cell) = &cells[1];
+    assert!(*id == 1 && *xyz == [1.5, 0.5, 0.5] && *r == 0.4);
+    assert!((cell.volume() - 0.9).abs() < 1e-12);
+    con.clear();
+    assert!(con.total_particles() == 0);
+    assert!(con.cells().count() == 0);
+    match ContainerPoly::new(&[0.0; 3], &[1.0; 3], &[0, 1, 1], &[false; 3], 8) {
+        Err(Error::InvalidContainer(msg)) => assert!(msg.contains("grid")),
+        _ => panic!(),
+    }
+}
+
+/// Check that the same code can compute the cells of both container variants.
+#[test]
+fn generic_over_containers() {
+    use crate::{Container, VoronoiCellBase};
+    fn total_volume(con: &impl ContainerBase) -> f64 {
+        con.cells()
+            .map(Result::unwrap)
+            .map(|(_, _, _, cell)| cell.volume())
+            .sum()
+    }
+    let mut mono = Container::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    let mut poly = ContainerPoly::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    for (i, x) in [0.2, 0.4, 0.9].iter().enumerate() {
+        mono.put(i as i32, &[*x, 0.5, 0.5]).unwrap();
+        poly.put(i as i32, &[*x, 0.5, 0.5], 0.1 * (i + 1) as f64)
+            .unwrap();
+    }
+    assert!((total_volume(&mono) - 1.0).abs() < 1e-12);
+    assert!((total_volume(&poly) - 1.0).abs() < 1e-12);
+}
+
+/// Check that the cells of a spherical droplet are cut by the wall, which is
+/// approximated by the plane tangent to the sphere nearest to each particle.
+#[test]
+fn spherical_wall() {
+    use crate::{VoronoiCellBase, WallSphere};
+    let mut con = ContainerPoly::new(&[-2.0; 3], &[2.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.add_wall(WallSphere::new(&[0.0; 3], 1.0, -7)).unwrap();
+    con.put(0, &[0.5, 0.0, 0.0], 0.3).unwrap();
+    con.put(1, &[-0.5, 0.0, 0.0], 0.3).unwrap();
+    for (_, xyz, _, cell) in con.cells_neighbor().map(Result::unwrap) {
+        assert!(cell.neighbors().contains(&-7));
+        assert!(cell
+            .vertices()
+            .iter()
+            .all(|v| (xyz[0] + v[0]).abs() <= 1.0 + 1e-9));
+        assert!(cell.volume() < 2.0 * 4.0 * 4.0);
+    }
+}
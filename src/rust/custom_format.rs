@@ -0,0 +1,159 @@
+use crate::rust::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::str::FromStr;
+
+/// The control sequences of the custom output format, with their meanings.
+pub const CONTROL_SEQUENCES: &[(char, &str)] = &[
+    // Particle-related
+    ('i', "The particle ID number"),
+    ('x', "The x coordinate of the particle"),
+    ('y', "The y coordinate of the particle"),
+    ('z', "The z coordinate of the particle"),
+    ('q', "The position vector of the particle, short for \"%x %y %z\""),
+    ('r', "The radius of the particle"),
+    // Vertex-related
+    ('w', "The number of vertices in the Voronoi cell"),
+    ('p', "A list of the vertices of the Voronoi cell in the format (x,y,z), relative to the particle center"),
+    ('P', "A list of the vertices of the Voronoi cell in the format (x,y,z), relative to the global coordinate system"),
+    ('o', "A list of the orders of each vertex"),
+    ('m', "The maximum radius squared of a vertex position, relative to the particle center"),
+    // Edge-related
+    ('g', "The number of edges of the Voronoi cell"),
+    ('E', "The total edge distance"),
+    ('e', "A list of perimeters of each face"),
+    // Face-related
+    ('s', "The number of faces of the Voronoi cell"),
+    ('F', "The total surface area of the Voronoi cell"),
+    ('A', "A frequency table of the number of edges for each face"),
+    ('a', "A list of the number of edges for each face"),
+    ('f', "A list of areas of each face"),
+    ('t', "A list of bracketed sequences of vertices that make up each face"),
+    ('l', "A list of normal vectors for each face"),
+    ('n', "A list of neighboring particle or wall IDs corresponding to each face"),
+    // Volume-related
+    ('v', "The volume of the Voronoi cell"),
+    ('c', "The centroid of the Voronoi cell, relative to the particle center"),
+    ('C', "The centroid of the Voronoi cell, in the global coordinate system"),
+];
+
+/// A validated format string for the custom output of Voronoi cells, see
+/// methods `VoronoiCellBase::output_custom` and `ContainerBase::print_custom`.
+///
+/// The format string is similar to the standard C function printf(). It is
+/// made up of text with additional control sequences that begin with a
+/// percentage sign, which are expanded to different statistics about each
+/// cell. See the list `CONTROL_SEQUENCES` and
+/// <http://math.lbl.gov/voro++/doc/custom.html> for more information.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CustomFormat {
+    format: CString,
+}
+
+impl CustomFormat {
+    /// Checks that a format string is valid.
+    ///
+    /// __Returns:__
+    ///     An error if the format string contains an unknown control
+    ///     sequence, ends in the middle of a control sequence, or contains a
+    ///     null character.
+    pub fn new(format: &str) -> Result<Self, Error> {
+        let mut chars = format.char_indices();
+        while let Some((_, c)) = chars.next() {
+            match c {
+                '%' => match chars.next() {
+                    Some((_, code)) if CONTROL_SEQUENCES.iter().any(|&(c, _)| c == code) => {}
+                    Some((i, code)) => {
+                        return Err(Error::InvalidFormat(format!(
+                            "unknown control sequence \"%{}\" at position {}",
+                            code,
+                            i - 1
+                        )))
+                    }
+                    None => {
+                        return Err(Error::InvalidFormat(
+                            "format ends with an incomplete control sequence".to_string(),
+                        ))
+                    }
+                },
+                '\0' => {
+                    return Err(Error::InvalidFormat(
+                        "format contains a null character".to_string(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            format: CString::new(format).unwrap(),
+        })
+    }
+
+    /// Returns the format string.
+    pub fn as_str(&self) -> &str {
+        self.format.to_str().unwrap()
+    }
+
+    /// Tests whether the format uses the neighbor information of the cells,
+    /// with the control sequence "%n".
+    pub fn uses_neighbors(&self) -> bool {
+        self.control_sequences().any(|code| code == 'n')
+    }
+
+    /// Iterates over the characters of the control sequences in the format,
+    /// in order.
+    pub(crate) fn control_sequences(&self) -> impl Iterator<Item = char> + '_ {
+        let mut chars = self.as_str().chars();
+        std::iter::from_fn(move || {
+            while let Some(c) = chars.next() {
+                if c == '%' {
+                    return chars.next();
+                }
+            }
+            None
+        })
+    }
+
+    pub(crate) fn as_c_str(&self) -> &CStr {
+        &self.format
+    }
+}
+
+impl FromStr for CustomFormat {
+    type Err = Error;
+    fn from_str(format: &str) -> Result<Self, Error> {
+        Self::new(format)
+    }
+}
+
+impl fmt::Display for CustomFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    let format = CustomFormat::new("%i %q %v %n %a %f").unwrap();
+    assert!(format.as_str() == "%i %q %v %n %a %f");
+    assert!(format.to_string() == "%i %q %v %n %a %f");
+    assert!(format.uses_neighbors());
+    assert!("%i: 100% %v".parse::<CustomFormat>().is_err());
+    assert!(!"%i %v".parse::<CustomFormat>().unwrap().uses_neighbors());
+    assert!(CustomFormat::new("no control sequences").is_ok());
+    assert!(CustomFormat::new("").is_ok());
+    match CustomFormat::new("%i %k") {
+        Err(Error::InvalidFormat(msg)) => {
+            assert!(msg == "unknown control sequence \"%k\" at position 3")
+        }
+        _ => panic!(),
+    }
+    assert!(CustomFormat::new("%i %").is_err());
+    assert!(CustomFormat::new("%%").is_err());
+    assert!(CustomFormat::new("%i\0").is_err());
+    assert!(CustomFormat::new("%é").is_err());
+    for &(code, _) in CONTROL_SEQUENCES {
+        assert!(CustomFormat::new(&format!("%{}", code)).is_ok());
+    }
+}
@@ -0,0 +1,218 @@
+use cpp::cpp;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
+
+cpp! {{
+    #include <new>
+    #include <string>
+    #include "voro++.hh"
+    using namespace voro;
+
+    // Runs a function, and catches any errors which it throws. The errors are
+    // stored into a Rust "Option<Error>". Returns true if the function
+    // finished without errors.
+    template<class F>
+    bool voroxx_try(void *error, F f) {
+        int status;
+        // The message is copied, since the exception is destroyed at the end
+        // of its catch block.
+        std::string message;
+        try {
+            f();
+            return true;
+        } catch(voro_error &e) {
+            status = e.status;
+            message = e.what();
+        } catch(std::bad_alloc &e) {
+            status = VOROPP_MEMORY_ERROR;
+            message = "Memory allocation failed";
+        }
+        const char *msg = message.c_str();
+        rust!(voroxx_set_error [error: &mut Option<Error> as "void*",
+                                status: i32 as "int", msg: *const c_char as "const char*"] {
+            *error = Some(Error::new(status, &CStr::from_ptr(msg).to_string_lossy()));
+        });
+        return false;
+    }
+
+    // Throws an error which was returned by Rust code, so that it propagates
+    // through the C++ code which called into Rust. Frees the message, which
+    // must have been made by the Rust function "Error::into_raw".
+    void voroxx_throw_rust_error(int status, char *msg) {
+        std::string s(msg);
+        rust!(voroxx_free_error_message [msg: *mut c_char as "char*"] {
+            drop(CString::from_raw(msg));
+        });
+        throw voro_error(s.c_str(), status);
+    }
+}}
+
+/// These status codes are defined in the file "config.hh".
+const VOROPP_FILE_ERROR: i32 = 1;
+const VOROPP_MEMORY_ERROR: i32 = 2;
+const VOROPP_INTERNAL_ERROR: i32 = 3;
+const VOROPP_DUPLICATE_ERROR: i32 = 5;
+/// These status codes are only used for passing errors through C++ code.
+const VOROXX_OUT_OF_BOUNDS: i32 = -1;
+const VOROXX_INVALID_POLYHEDRON: i32 = -2;
+const VOROXX_INVALID_FORMAT: i32 = -3;
+const VOROXX_INVALID_LATTICE: i32 = -4;
+const VOROXX_INVALID_CONTAINER: i32 = -5;
+
+/// The errors which Voro++ can encounter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// One of the absolute maximum memory limits in the file "config.hh" was
+    /// exceeded, such as `max_vertices`, `max_vertex_order`, or
+    /// `max_particle_memory`, or else memory allocation failed.
+    MemoryLimit(String),
+
+    /// Voro++ detected that its representation of a Voronoi cell is
+    /// inconsistent. This generally indicates a bug, or numerical problems
+    /// with the input.
+    Internal(String),
+
+    /// A file-related error, such as not being able to open a file.
+    File(String),
+
+    /// A particle was put outside of the bounds of a non-periodic container.
+    OutOfBounds,
+
+    /// A particle was put into a periodic container at the same position as
+    /// another particle.
+    Duplicate(String),
+
+    /// The vertices and faces which were given for initializing a Voronoi
+    /// cell do not describe a convex polyhedron.
+    InvalidPolyhedron(String),
+
+    /// A format string for the custom output of Voronoi cells is not valid.
+    InvalidFormat(String),
+
+    /// The periodicity vectors which were given for a periodic container or
+    /// a unit cell do not describe a valid lattice.
+    InvalidLattice(String),
+
+    /// The bounds, the grid of computational blocks, or the initial memory
+    /// allocation which were given for a container are not valid.
+    InvalidContainer(String),
+}
+
+impl Error {
+    /// Converts a status code and message from the C++ code into an error.
+    pub(crate) fn new(status: i32, msg: &str) -> Self {
+        let msg = msg.to_string();
+        match status {
+            VOROPP_FILE_ERROR => Error::File(msg),
+            VOROPP_MEMORY_ERROR => Error::MemoryLimit(msg),
+            VOROPP_DUPLICATE_ERROR => Error::Duplicate(msg),
+            VOROXX_OUT_OF_BOUNDS => Error::OutOfBounds,
+            VOROXX_INVALID_POLYHEDRON => Error::InvalidPolyhedron(msg),
+            VOROXX_INVALID_FORMAT => Error::InvalidFormat(msg),
+            VOROXX_INVALID_LATTICE => Error::InvalidLattice(msg),
+            VOROXX_INVALID_CONTAINER => Error::InvalidContainer(msg),
+            _ => Error::Internal(msg),
+        }
+    }
+
+    /// Converts an error into a status code and a message, for passing it to
+    /// the C++ function "voroxx_throw_rust_error".
+    pub(crate) fn into_raw(self) -> (i32, *mut c_char) {
+        let status = match &self {
+            Error::File(_) => VOROPP_FILE_ERROR,
+            Error::MemoryLimit(_) => VOROPP_MEMORY_ERROR,
+            Error::Internal(_) => VOROPP_INTERNAL_ERROR,
+            Error::OutOfBounds => VOROXX_OUT_OF_BOUNDS,
+            Error::Duplicate(_) => VOROPP_DUPLICATE_ERROR,
+            Error::InvalidPolyhedron(_) => VOROXX_INVALID_POLYHEDRON,
+            Error::InvalidFormat(_) => VOROXX_INVALID_FORMAT,
+            Error::InvalidLattice(_) => VOROXX_INVALID_LATTICE,
+            Error::InvalidContainer(_) => VOROXX_INVALID_CONTAINER,
+        };
+        let msg = match self {
+            Error::File(msg)
+            | Error::MemoryLimit(msg)
+            | Error::Internal(msg)
+            | Error::Duplicate(msg)
+            | Error::InvalidPolyhedron(msg)
+            | Error::InvalidFormat(msg)
+            | Error::InvalidLattice(msg)
+            | Error::InvalidContainer(msg) => msg,
+            Error::OutOfBounds => String::new(),
+        };
+        let msg = CString::new(msg.replace('\0', "")).unwrap();
+        (status, msg.into_raw())
+    }
+
+    /// Returns the result of a call to "voroxx_try".
+    pub(crate) fn check<T>(value: T, error: Option<Error>) -> Result<T, Error> {
+        match error {
+            None => Ok(value),
+            Some(error) => Err(error),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MemoryLimit(msg) => write!(f, "memory limit exceeded: {}", msg),
+            Error::Internal(msg) => write!(f, "internal error: {}", msg),
+            Error::File(msg) => write!(f, "file error: {}", msg),
+            Error::OutOfBounds => write!(f, "particle is outside of the container"),
+            Error::Duplicate(msg) => write!(f, "duplicate particle: {}", msg),
+            Error::InvalidPolyhedron(msg) => write!(f, "invalid polyhedron: {}", msg),
+            Error::InvalidFormat(msg) => write!(f, "invalid format: {}", msg),
+            Error::InvalidLattice(msg) => write!(f, "invalid lattice: {}", msg),
+            Error::InvalidContainer(msg) => write!(f, "invalid container: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Check that errors are returned instead of terminating the program, both
+/// from the C++ code and from walls which are implemented in Rust.
+#[test]
+fn recoverable_errors() {
+    use crate::{Container, ContainerBase, ContainerPeriodic, VoronoiCellBase, Wall};
+    let cube = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut con = ContainerPeriodic::new(&cube, &[1, 1, 1], 8).unwrap();
+    con.put(0, &[0.5, 0.5, 0.5]).unwrap();
+    match con.put(1, &[1.5, 0.5, 0.5]) {
+        Err(Error::Duplicate(msg)) => assert!(msg.starts_with("Duplicate")),
+        _ => panic!(),
+    }
+    assert!(con.total_particles() == 1);
+
+    struct Broken;
+    impl Wall for Broken {
+        fn point_inside(&self, _xyz: &[f64; 3]) -> bool {
+            true
+        }
+        fn cut_cell(&self, _cell: &mut dyn VoronoiCellBase, xyz: &[f64; 3]) -> Result<bool, Error> {
+            if xyz[0] > 0.5 {
+                Err(Error::Internal("broken wall".to_string()))
+            } else {
+                Ok(true)
+            }
+        }
+    }
+    let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.add_wall(Broken).unwrap();
+    con.put(0, &[0.25, 0.5, 0.5]).unwrap();
+    con.put(1, &[0.75, 0.5, 0.5]).unwrap();
+    let results: Vec<_> = con.cells().collect();
+    assert!(results.len() == 2);
+    assert!(results[0].is_ok());
+    let error = Error::Internal("broken wall".to_string());
+    assert!(results[1].as_ref().err() == Some(&error));
+    assert!(error.to_string() == "internal error: broken wall");
+    let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.add_wall(Broken).unwrap();
+    con.put(0, &[0.75, 0.5, 0.5]).unwrap();
+    con.put(1, &[0.25, 0.5, 0.5]).unwrap();
+    // The iterator stops after the first error.
+    assert!(con.cells().count() == 1);
+}
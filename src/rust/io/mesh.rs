@@ -0,0 +1,313 @@
+//! Polygon meshes in the Wavefront OBJ, STL, and PLY file formats.
+//!
+//! Build a `Mesh` from one or more Voronoi cells, and then write it with one of
+//! its `write_*` methods. The faces of the mesh are ordered counterclockwise
+//! as seen from outside of each cell, which is the convention of these file
+//! formats, so that their normal vectors point out of the cells.
+
+use crate::rust::container_base::ContainerBase;
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell_base::{VoronoiCellBase, UNKNOWN_NEIGHBOR};
+use std::io::{self, Write};
+
+/// A collection of polygons, with the IDs of the particle and the neighbor
+/// which each polygon belongs to.
+///
+/// Each Voronoi cell has its own copy of its vertices, so the faces which are
+/// shared by two neighboring cells appear twice in the mesh, once for each
+/// cell.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Mesh {
+    /// The vertex coordinates.
+    pub vertices: Vec<[f64; 3]>,
+
+    /// The polygons.
+    pub faces: Vec<MeshFace>,
+}
+
+/// A polygon of a `Mesh`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MeshFace {
+    /// The vertices of the polygon, as indices into the vertices list of the
+    /// mesh, ordered counterclockwise as seen from outside of the cell.
+    pub vertices: Vec<usize>,
+
+    /// The ID of the particle whose Voronoi cell this face belongs to.
+    pub particle: i32,
+
+    /// The neighbor information of this face, or `UNKNOWN_NEIGHBOR` if the
+    /// cell does not track neighbor information.
+    pub neighbor: i32,
+}
+
+impl Mesh {
+    /// Makes an empty mesh.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes a mesh of a single Voronoi cell, in the local coordinate system
+    /// of the cell, with particle ID zero.
+    pub fn from_cell<C: VoronoiCellBase + ?Sized>(cell: &C) -> Self {
+        let mut mesh = Self::new();
+        mesh.add_cell(0, &[0.0; 3], cell);
+        mesh
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// makes a mesh of them with neighbor information.
+    ///
+    /// __Returns:__
+    ///     An error if the computation of a cell failed.
+    pub fn from_container<T: ContainerBase>(container: &T) -> Result<Self, Error> {
+        let mut mesh = Self::new();
+        for result in container.cells_neighbor() {
+            let (id, position, _radius, cell) = result?;
+            mesh.add_cell(id, &position, &cell);
+        }
+        Ok(mesh)
+    }
+
+    /// Adds a Voronoi cell to the mesh.
+    ///
+    /// __Parameters:__
+    /// * `id` The ID of the particle.
+    /// * `position` The position of the particle, which is added to the
+    ///   vertices of the cell.
+    /// * `cell` The Voronoi cell of the particle.
+    pub fn add_cell<C: VoronoiCellBase + ?Sized>(
+        &mut self,
+        id: i32,
+        position: &[f64; 3],
+        cell: &C,
+    ) {
+        let geometry = cell.geometry();
+        let offset = self.vertices.len();
+        self.vertices
+            .extend(geometry.vertices.iter().map(|v| add(v, position)));
+        for (i, face) in geometry.faces.iter().enumerate() {
+            self.faces.push(MeshFace {
+                vertices: face.iter().rev().map(|&v| v + offset).collect(),
+                particle: id,
+                neighbor: geometry
+                    .neighbors
+                    .as_ref()
+                    .map_or(UNKNOWN_NEIGHBOR, |n| n[i]),
+            });
+        }
+    }
+
+    /// Returns a copy of the mesh in which every polygon is divided into
+    /// triangles. The faces of Voronoi cells are convex, so each polygon is
+    /// divided into a fan of triangles around its first vertex.
+    pub fn triangulate(&self) -> Self {
+        let mut faces = Vec::with_capacity(self.number_of_triangles());
+        for face in &self.faces {
+            for i in 2..face.vertices.len() {
+                faces.push(MeshFace {
+                    vertices: vec![face.vertices[0], face.vertices[i - 1], face.vertices[i]],
+                    ..*face
+                });
+            }
+        }
+        Self {
+            vertices: self.vertices.clone(),
+            faces,
+        }
+    }
+
+    /// Counts the number of triangles which the method `triangulate` makes.
+    pub fn number_of_triangles(&self) -> usize {
+        self.faces
+            .iter()
+            .map(|f| f.vertices.len().saturating_sub(2))
+            .sum()
+    }
+
+    /// Writes the mesh in the Wavefront OBJ format. The faces of each
+    /// particle are grouped into an object named "particle_`id`".
+    pub fn write_obj<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "# voroxx")?;
+        for v in &self.vertices {
+            writeln!(w, "v {} {} {}", v[0], v[1], v[2])?;
+        }
+        let mut particle = None;
+        for face in &self.faces {
+            if particle != Some(face.particle) {
+                particle = Some(face.particle);
+                writeln!(w, "o particle_{}", face.particle)?;
+            }
+            write!(w, "f")?;
+            for v in &face.vertices {
+                write!(w, " {}", v + 1)?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()
+    }
+
+    /// Writes the mesh in the ASCII STL format, after dividing every polygon
+    /// into triangles. The STL format does not store the particle and
+    /// neighbor IDs.
+    pub fn write_stl_ascii<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "solid voroxx")?;
+        for (normal, triangle) in self.triangles() {
+            writeln!(w, "facet normal {} {} {}", normal[0], normal[1], normal[2])?;
+            writeln!(w, "outer loop")?;
+            for v in &triangle {
+                writeln!(w, "vertex {} {} {}", v[0], v[1], v[2])?;
+            }
+            writeln!(w, "endloop")?;
+            writeln!(w, "endfacet")?;
+        }
+        writeln!(w, "endsolid voroxx")?;
+        w.flush()
+    }
+
+    /// Writes the mesh in the binary STL format, after dividing every polygon
+    /// into triangles. The STL format stores the coordinates in single
+    /// precision, and it does not store the particle and neighbor IDs.
+    pub fn write_stl_binary<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut header = [0u8; 80];
+        header[..7].copy_from_slice(b"voroxx\n");
+        w.write_all(&header)?;
+        let count = self.number_of_triangles() as u32;
+        w.write_all(&count.to_le_bytes())?;
+        for (normal, triangle) in self.triangles() {
+            for v in std::iter::once(&normal).chain(&triangle) {
+                for x in v {
+                    w.write_all(&(*x as f32).to_le_bytes())?;
+                }
+            }
+            w.write_all(&[0, 0])?;
+        }
+        w.flush()
+    }
+
+    /// Writes the mesh in the ASCII PLY format. Each face has the integer
+    /// properties "particle" and "neighbor".
+    pub fn write_ply<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "ply")?;
+        writeln!(w, "format ascii 1.0")?;
+        writeln!(w, "comment voroxx")?;
+        writeln!(w, "element vertex {}", self.vertices.len())?;
+        writeln!(w, "property double x")?;
+        writeln!(w, "property double y")?;
+        writeln!(w, "property double z")?;
+        writeln!(w, "element face {}", self.faces.len())?;
+        writeln!(w, "property list uchar int vertex_indices")?;
+        writeln!(w, "property int particle")?;
+        writeln!(w, "property int neighbor")?;
+        writeln!(w, "end_header")?;
+        for v in &self.vertices {
+            writeln!(w, "{} {} {}", v[0], v[1], v[2])?;
+        }
+        for face in &self.faces {
+            write!(w, "{}", face.vertices.len())?;
+            for v in &face.vertices {
+                write!(w, " {}", v)?;
+            }
+            writeln!(w, " {} {}", face.particle, face.neighbor)?;
+        }
+        w.flush()
+    }
+
+    /// Iterates over the triangles of the mesh, with their unit normal
+    /// vectors.
+    fn triangles(&self) -> impl Iterator<Item = ([f64; 3], [[f64; 3]; 3])> + '_ {
+        self.faces.iter().flat_map(move |face| {
+            let v = &face.vertices;
+            (2..v.len()).map(move |i| {
+                let triangle = [
+                    self.vertices[v[0]],
+                    self.vertices[v[i - 1]],
+                    self.vertices[v[i]],
+                ];
+                (normal(&triangle), triangle)
+            })
+        })
+    }
+}
+
+fn add(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// Calculates the unit normal vector of a counterclockwise triangle.
+fn normal(t: &[[f64; 3]; 3]) -> [f64; 3] {
+    let u = [t[1][0] - t[0][0], t[1][1] - t[0][1], t[1][2] - t[0][2]];
+    let v = [t[2][0] - t[0][0], t[2][1] - t[0][1], t[2][2] - t[0][2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if len > 0.0 {
+        [n[0] / len, n[1] / len, n[2] / len]
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::{Container, VoronoiCell};
+    let cube = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let mesh = Mesh::from_cell(&cube);
+    assert!(mesh.vertices.len() == 8);
+    assert!(mesh.faces.len() == 6);
+    assert!(mesh.faces.iter().all(|f| f.neighbor == UNKNOWN_NEIGHBOR));
+    assert!(mesh.number_of_triangles() == 12);
+    let triangles = mesh.triangulate();
+    assert!(triangles.faces.len() == 12);
+    assert!(triangles.faces.iter().all(|f| f.vertices.len() == 3));
+    // The normal vectors point out of the cell.
+    for (n, t) in mesh.triangles() {
+        assert!(n[0] * t[0][0] + n[1] * t[0][1] + n[2] * t[0][2] == 1.0);
+    }
+
+    let mut obj = vec![];
+    mesh.write_obj(&mut obj).unwrap();
+    let obj = String::from_utf8(obj).unwrap();
+    assert!(obj.lines().filter(|l| l.starts_with("v ")).count() == 8);
+    assert!(obj.lines().filter(|l| l.starts_with("f ")).count() == 6);
+    assert!(obj.contains("o particle_0\n"));
+    let mut ply = vec![];
+    mesh.write_ply(&mut ply).unwrap();
+    let ply = String::from_utf8(ply).unwrap();
+    let unknown = format!(" 0 {}", UNKNOWN_NEIGHBOR);
+    assert!(ply.lines().filter(|l| l.ends_with(&unknown)).count() == 6);
+
+    let mut stl = vec![];
+    mesh.write_stl_ascii(&mut stl).unwrap();
+    let stl = String::from_utf8(stl).unwrap();
+    assert!(stl.starts_with("solid voroxx\n"));
+    assert!(stl.matches("endfacet").count() == 12);
+    let mut stl = vec![];
+    mesh.write_stl_binary(&mut stl).unwrap();
+    assert!(stl.len() == 84 + 12 * 50);
+    assert!(stl[80..84] == 12u32.to_le_bytes());
+
+    let mut con = Container::new(&[0.0; 3], &[2.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.5, 1.0, 1.0]).unwrap();
+    con.put(8, &[1.5, 1.0, 1.0]).unwrap();
+    let mesh = Mesh::from_container(&con).unwrap();
+    assert!(mesh.vertices.len() == 16);
+    assert!(mesh.faces.len() == 12);
+    assert!(mesh.vertices.contains(&[2.0, 2.0, 2.0]));
+    let face = mesh
+        .faces
+        .iter()
+        .find(|f| f.particle == 7 && f.neighbor == 8)
+        .unwrap();
+    assert!(face.vertices.iter().all(|&v| mesh.vertices[v][0] == 1.0));
+    let mut ply = vec![];
+    mesh.write_ply(&mut ply).unwrap();
+    let ply = String::from_utf8(ply).unwrap();
+    assert!(ply.contains("element vertex 16\n"));
+    assert!(ply.contains("element face 12\n"));
+    assert!(ply.lines().filter(|l| l.ends_with(" 7 8")).count() == 1);
+    assert!(ply.lines().filter(|l| l.ends_with(" 8 7")).count() == 1);
+}
@@ -0,0 +1,5 @@
+//! Export Voronoi cells and tessellations to files.
+
+pub mod mesh;
+pub mod table;
+pub mod vtu;
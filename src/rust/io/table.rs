@@ -0,0 +1,462 @@
+//! Tables of per-particle statistics in the CSV, Apache Arrow, and Apache
+//! Parquet formats.
+//!
+//! Build a `StatsTable` of `CellStats`, with one row per particle and one
+//! column per selected statistic, and then write it with one of its methods.
+//! The Arrow and Parquet formats require the cargo features `arrow` and
+//! `parquet` respectively.
+//!
+//! The columns are "id", "x", "y", "z", and "radius", followed by the
+//! selected statistics in the order of the fields of `CellStats`. Each column
+//! is named after its field, except that the centroid is split into the
+//! columns "centroid_x", "centroid_y", and "centroid_z".
+
+use crate::rust::cell_stats::{CellStats, CellStatsBuilder, STATISTICS};
+use crate::rust::container_base::ContainerBase;
+use crate::rust::error::Error;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+#[cfg(feature = "arrow")]
+use arrow_array::{
+    builder::{FixedSizeListBuilder, Float64Builder, Int32Builder, ListBuilder},
+    types::{Float64Type, Int32Type},
+    ArrayRef, Float64Array, Int32Array, ListArray, RecordBatch,
+};
+#[cfg(feature = "arrow")]
+use arrow_schema::ArrowError;
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+
+/// A table of the statistics of many particles.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StatsTable {
+    /// The statistics which have a column in the table.
+    pub selection: CellStatsBuilder,
+
+    /// The statistics of each particle. Statistics which are in the selection
+    /// but missing from a row are written as empty fields in the CSV format,
+    /// and as nulls in the Arrow and Parquet formats.
+    pub rows: Vec<CellStats>,
+}
+
+impl StatsTable {
+    /// Makes an empty table with the given columns.
+    pub fn new(selection: CellStatsBuilder) -> Self {
+        Self {
+            selection,
+            rows: vec![],
+        }
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// makes a table of their selected statistics.
+    ///
+    /// __Returns:__
+    ///     An error if the computation of a cell failed.
+    pub fn from_container<T: ContainerBase>(
+        container: &T,
+        selection: CellStatsBuilder,
+    ) -> Result<Self, Error> {
+        let rows = selection.compute_container(container)?;
+        Ok(Self { selection, rows })
+    }
+
+    /// Returns the names of the columns.
+    pub fn column_names(&self) -> Vec<&'static str> {
+        let mut names = vec!["id", "x", "y", "z", "radius"];
+        for code in self.codes() {
+            names.extend_from_slice(column_names(code));
+        }
+        names
+    }
+
+    /// Writes the table in the CSV format, with a header line of the column
+    /// names. The numbers are written with enough digits to be read back
+    /// exactly. The values of a list are separated by spaces, and the
+    /// elements of a list of lists (such as the vertices, normals, and face
+    /// vertices) are separated by semicolons, so that no field needs quotes.
+    pub fn write_csv<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "{}", self.column_names().join(","))?;
+        let codes = self.codes();
+        for s in &self.rows {
+            let p = &s.position;
+            write!(w, "{},{},{},{},{}", s.id, p[0], p[1], p[2], s.radius)?;
+            for &code in &codes {
+                write!(w, ",")?;
+                write_csv_fields(&mut w, s, code)?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()
+    }
+
+    /// Converts the table into an Arrow record batch. The lists are stored
+    /// in list-typed columns, and the vertices and normals are stored as
+    /// lists of fixed size lists of three numbers.
+    #[cfg(feature = "arrow")]
+    pub fn record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let rows = &self.rows;
+        let mut columns: Vec<(&str, ArrayRef, bool)> = vec![
+            (
+                "id",
+                Arc::new(Int32Array::from_iter_values(rows.iter().map(|s| s.id))),
+                false,
+            ),
+            (
+                "x",
+                float_column(rows.iter().map(|s| Some(s.position[0]))),
+                false,
+            ),
+            (
+                "y",
+                float_column(rows.iter().map(|s| Some(s.position[1]))),
+                false,
+            ),
+            (
+                "z",
+                float_column(rows.iter().map(|s| Some(s.position[2]))),
+                false,
+            ),
+            (
+                "radius",
+                float_column(rows.iter().map(|s| Some(s.radius))),
+                false,
+            ),
+        ];
+        for code in self.codes() {
+            let arrays = match code {
+                'w' => vec![int_column(rows.iter().map(|s| s.number_of_vertices))],
+                'p' => vec![triples_column(rows.iter().map(|s| s.vertices.as_ref()))],
+                'o' => vec![int_list_column(
+                    rows.iter().map(|s| s.vertex_orders.as_ref()),
+                )],
+                'm' => vec![float_column(rows.iter().map(|s| s.max_radius_squared))],
+                'g' => vec![int_column(rows.iter().map(|s| s.number_of_edges))],
+                'E' => vec![float_column(rows.iter().map(|s| s.total_edge_distance))],
+                'e' => vec![float_list_column(
+                    rows.iter().map(|s| s.face_perimeters.as_ref()),
+                )],
+                's' => vec![int_column(rows.iter().map(|s| s.number_of_faces))],
+                'F' => vec![float_column(rows.iter().map(|s| s.surface_area))],
+                'A' => vec![int_list_column(
+                    rows.iter().map(|s| s.face_freq_table.as_ref()),
+                )],
+                'a' => vec![int_list_column(rows.iter().map(|s| s.face_orders.as_ref()))],
+                'f' => vec![float_list_column(
+                    rows.iter().map(|s| s.face_areas.as_ref()),
+                )],
+                't' => vec![faces_column(rows.iter().map(|s| s.face_vertices.as_ref()))],
+                'l' => vec![triples_column(rows.iter().map(|s| s.normals.as_ref()))],
+                'n' => vec![int_list_column(rows.iter().map(|s| s.neighbors.as_ref()))],
+                'v' => vec![float_column(rows.iter().map(|s| s.volume))],
+                'c' => (0..3)
+                    .map(|i| float_column(rows.iter().map(|s| s.centroid.map(|c| c[i]))))
+                    .collect(),
+                _ => unreachable!(),
+            };
+            for (&name, array) in column_names(code).iter().zip(arrays) {
+                columns.push((name, array, true));
+            }
+        }
+        RecordBatch::try_from_iter_with_nullable(columns)
+    }
+
+    /// Writes the table in the Parquet format, as a single row group.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: Write + Send>(
+        &self,
+        w: W,
+    ) -> Result<(), parquet::errors::ParquetError> {
+        let batch = self.record_batch()?;
+        let mut writer = parquet::arrow::ArrowWriter::try_new(w, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    /// Returns the control sequence characters of the selected statistics,
+    /// in the order of their columns.
+    fn codes(&self) -> Vec<char> {
+        STATISTICS
+            .iter()
+            .copied()
+            .filter(|&code| self.selection.selects(code))
+            .collect()
+    }
+}
+
+/// Returns the names of the columns of a statistic.
+fn column_names(code: char) -> &'static [&'static str] {
+    match code {
+        'w' => &["number_of_vertices"],
+        'p' => &["vertices"],
+        'o' => &["vertex_orders"],
+        'm' => &["max_radius_squared"],
+        'g' => &["number_of_edges"],
+        'E' => &["total_edge_distance"],
+        'e' => &["face_perimeters"],
+        's' => &["number_of_faces"],
+        'F' => &["surface_area"],
+        'A' => &["face_freq_table"],
+        'a' => &["face_orders"],
+        'f' => &["face_areas"],
+        't' => &["face_vertices"],
+        'l' => &["normals"],
+        'n' => &["neighbors"],
+        'v' => &["volume"],
+        'c' => &["centroid_x", "centroid_y", "centroid_z"],
+        _ => unreachable!(),
+    }
+}
+
+/// Writes the CSV fields of a statistic, without the leading comma.
+fn write_csv_fields<W: Write>(w: &mut W, s: &CellStats, code: char) -> io::Result<()> {
+    match code {
+        'w' => write_scalar(w, &s.number_of_vertices),
+        'p' => write_nested(w, &s.vertices),
+        'o' => write_list(w, &s.vertex_orders),
+        'm' => write_scalar(w, &s.max_radius_squared),
+        'g' => write_scalar(w, &s.number_of_edges),
+        'E' => write_scalar(w, &s.total_edge_distance),
+        'e' => write_list(w, &s.face_perimeters),
+        's' => write_scalar(w, &s.number_of_faces),
+        'F' => write_scalar(w, &s.surface_area),
+        'A' => write_list(w, &s.face_freq_table),
+        'a' => write_list(w, &s.face_orders),
+        'f' => write_list(w, &s.face_areas),
+        't' => write_nested(w, &s.face_vertices),
+        'l' => write_nested(w, &s.normals),
+        'n' => write_list(w, &s.neighbors),
+        'v' => write_scalar(w, &s.volume),
+        'c' => match &s.centroid {
+            Some(c) => write!(w, "{},{},{}", c[0], c[1], c[2]),
+            None => write!(w, ",,"),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn write_scalar<W: Write, T: Display>(w: &mut W, value: &Option<T>) -> io::Result<()> {
+    match value {
+        Some(value) => write!(w, "{}", value),
+        None => Ok(()),
+    }
+}
+
+fn write_list<W: Write, T: Display>(w: &mut W, list: &Option<Vec<T>>) -> io::Result<()> {
+    if let Some(list) = list {
+        write_values(w, list)?;
+    }
+    Ok(())
+}
+
+fn write_nested<W, T, V>(w: &mut W, lists: &Option<Vec<V>>) -> io::Result<()>
+where
+    W: Write,
+    T: Display,
+    V: AsRef<[T]>,
+{
+    if let Some(lists) = lists {
+        for (i, list) in lists.iter().enumerate() {
+            if i > 0 {
+                write!(w, ";")?;
+            }
+            write_values(w, list.as_ref())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_values<W: Write, T: Display>(w: &mut W, values: &[T]) -> io::Result<()> {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(w, " ")?;
+        }
+        write!(w, "{}", value)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "arrow")]
+fn int_column(values: impl Iterator<Item = Option<i32>>) -> ArrayRef {
+    Arc::new(values.collect::<Int32Array>())
+}
+
+#[cfg(feature = "arrow")]
+fn float_column(values: impl Iterator<Item = Option<f64>>) -> ArrayRef {
+    Arc::new(values.collect::<Float64Array>())
+}
+
+#[cfg(feature = "arrow")]
+fn int_list_column<'a>(lists: impl Iterator<Item = Option<&'a Vec<i32>>>) -> ArrayRef {
+    Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(
+        lists.map(|list| list.map(|list| list.iter().copied().map(Some))),
+    ))
+}
+
+#[cfg(feature = "arrow")]
+fn float_list_column<'a>(lists: impl Iterator<Item = Option<&'a Vec<f64>>>) -> ArrayRef {
+    Arc::new(ListArray::from_iter_primitive::<Float64Type, _, _>(
+        lists.map(|list| list.map(|list| list.iter().copied().map(Some))),
+    ))
+}
+
+#[cfg(feature = "arrow")]
+fn triples_column<'a>(lists: impl Iterator<Item = Option<&'a Vec<[f64; 3]>>>) -> ArrayRef {
+    let mut builder = ListBuilder::new(FixedSizeListBuilder::new(Float64Builder::new(), 3));
+    for list in lists {
+        if let Some(list) = list {
+            for triple in list {
+                builder.values().values().append_slice(triple);
+                builder.values().append(true);
+            }
+        }
+        builder.append(list.is_some());
+    }
+    Arc::new(builder.finish())
+}
+
+#[cfg(feature = "arrow")]
+fn faces_column<'a>(lists: impl Iterator<Item = Option<&'a Vec<Vec<usize>>>>) -> ArrayRef {
+    let mut builder = ListBuilder::new(ListBuilder::new(Int32Builder::new()));
+    for list in lists {
+        if let Some(list) = list {
+            for face in list {
+                for &v in face {
+                    builder.values().values().append_value(v as i32);
+                }
+                builder.values().append(true);
+            }
+        }
+        builder.append(list.is_some());
+    }
+    Arc::new(builder.finish())
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::Container;
+    let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.1 + 0.2, 0.5, 0.5]).unwrap();
+    con.put(8, &[0.7, 0.5, 0.5]).unwrap();
+    let selection = CellStats::builder()
+        .volume()
+        .neighbors()
+        .centroid()
+        .face_vertices();
+    let table = StatsTable::from_container(&con, selection.clone()).unwrap();
+    assert!(table.rows.len() == 2);
+    let names = table.column_names();
+    assert!(
+        names
+            == [
+                "id",
+                "x",
+                "y",
+                "z",
+                "radius",
+                "face_vertices",
+                "neighbors",
+                "volume",
+                "centroid_x",
+                "centroid_y",
+                "centroid_z"
+            ]
+    );
+
+    let mut csv = vec![];
+    table.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert!(lines.next() == Some(names.join(",").as_str()));
+    for (line, s) in lines.zip(&table.rows) {
+        let fields: Vec<&str> = line.split(',').collect();
+        assert!(fields.len() == names.len());
+        assert!(fields[0].parse::<i32>().unwrap() == s.id);
+        // The numbers are written with full precision.
+        assert!(fields[1].parse::<f64>().unwrap() == s.position[0]);
+        assert!(fields[7].parse::<f64>().unwrap() == s.volume.unwrap());
+        assert!(fields[8].parse::<f64>().unwrap() == s.centroid.unwrap()[0]);
+        let neighbors: Vec<i32> = fields[6].split(' ').map(|n| n.parse().unwrap()).collect();
+        assert!(Some(neighbors) == s.neighbors);
+        let faces: Vec<Vec<usize>> = fields[5]
+            .split(';')
+            .map(|f| f.split(' ').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        assert!(Some(faces) == s.face_vertices);
+    }
+    assert!(csv.contains("\n7,0.30000000000000004,0.5,0.5,0.5,"));
+
+    // Missing statistics are written as empty fields.
+    let mut table = StatsTable::new(selection);
+    table.rows.push(CellStats::default());
+    let mut csv = vec![];
+    table.write_csv(&mut csv).unwrap();
+    assert!(String::from_utf8(csv)
+        .unwrap()
+        .ends_with("\n0,0,0,0,0,,,,,,\n"));
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn arrow() {
+    use crate::Container;
+    use arrow_array::cast::AsArray;
+    use arrow_array::Array;
+    let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.3, 0.5, 0.5]).unwrap();
+    con.put(8, &[0.7, 0.5, 0.5]).unwrap();
+    let mut table = StatsTable::from_container(&con, CellStatsBuilder::all()).unwrap();
+    table.rows.push(CellStats::default());
+    let batch = table.record_batch().unwrap();
+    assert!(batch.num_rows() == 3);
+    assert!(batch.num_columns() == table.column_names().len());
+    let names: Vec<String> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect();
+    assert!(names == table.column_names());
+
+    let volume = batch
+        .column_by_name("volume")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    assert!(volume.value(0) == table.rows[0].volume.unwrap());
+    assert!(volume.is_null(2));
+    let neighbors = batch.column_by_name("neighbors").unwrap().as_list::<i32>();
+    let first = neighbors.value(0);
+    assert!(
+        first.as_primitive::<Int32Type>().values().to_vec()
+            == *table.rows[0].neighbors.as_ref().unwrap()
+    );
+    assert!(neighbors.is_null(2));
+    let vertices = batch.column_by_name("vertices").unwrap().as_list::<i32>();
+    assert!(vertices.value(1).len() == 8);
+    let faces = batch
+        .column_by_name("face_vertices")
+        .unwrap()
+        .as_list::<i32>();
+    assert!(faces.value(1).len() == 6);
+    assert!(faces.value(1).as_list::<i32>().value(0).len() == 4);
+
+    #[cfg(feature = "parquet")]
+    {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        let path =
+            std::env::temp_dir().join(format!("voroxx_stats_{}.parquet", std::process::id()));
+        table
+            .write_parquet(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+        assert!(batches == [batch]);
+    }
+}
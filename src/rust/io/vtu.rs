@@ -0,0 +1,479 @@
+//! Unstructured grids in the VTK XML file format (".vtu"), for ParaView.
+//!
+//! Each Voronoi cell is written as a `VTK_POLYHEDRON` cell. The vertices which
+//! are shared by neighboring cells are merged, so that the cells of a
+//! tessellation are connected to each other in the grid.
+//!
+//! The grid has the following cell data:
+//! * "ParticleID" The ID of the particle of each cell.
+//! * "Volume" The volume of each cell.
+//! * "SurfaceArea" The surface area of each cell.
+//! * "NumberOfFaces" The number of faces of each cell.
+//! * "FaceArea" The area of each face of each cell.
+//! * "FaceNeighbor" The neighbor information of each face of each cell, or
+//!   `UNKNOWN_NEIGHBOR` if the cell does not track neighbor information.
+//!
+//! VTK does not have a place for data about the faces of polyhedra, so the
+//! face data is stored with the cells. The arrays "FaceArea" and
+//! "FaceNeighbor" have one component for each face, in the same order as the
+//! faces of the cell are listed in the "faces" array. The number of
+//! components is the largest number of faces of any cell, and the components
+//! after the last face of a cell are padded with an area of zero and a
+//! neighbor of `UNKNOWN_NEIGHBOR`.
+
+use crate::rust::cell_geometry::CellGeometry;
+use crate::rust::container_base::ContainerBase;
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell_base::{VoronoiCellBase, UNKNOWN_NEIGHBOR};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// The cell type of a polyhedron in the VTK file format.
+const VTK_POLYHEDRON: u8 = 42;
+
+/// The ways of storing data arrays in a VTU file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Encoding {
+    /// Numbers written as text.
+    Ascii,
+
+    /// Little-endian binary numbers, encoded in base64.
+    Binary,
+}
+
+/// A collection of Voronoi cells, with shared vertices.
+#[derive(Debug, Clone, Default)]
+pub struct UnstructuredGrid {
+    tolerance: f64,
+    points: Vec<[f64; 3]>,
+    buckets: HashMap<[i64; 3], Vec<usize>>,
+    cells: Vec<GridCell>,
+}
+
+#[derive(Debug, Clone)]
+struct GridCell {
+    particle: i32,
+    volume: f64,
+    surface_area: f64,
+    points: Vec<usize>,
+    faces: Vec<Vec<usize>>,
+    face_areas: Vec<f64>,
+    face_neighbors: Vec<i32>,
+}
+
+impl UnstructuredGrid {
+    /// Makes an empty grid.
+    ///
+    /// __Parameters:__
+    /// * `tolerance` The distance within which two vertices are merged into
+    ///   a single point of the grid. The vertices of neighboring Voronoi
+    ///   cells are computed separately, so they can differ slightly.
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance,
+            ..Self::default()
+        }
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, and
+    /// makes a grid of them with neighbor information.
+    ///
+    /// __Parameters:__
+    /// * `container` The particles.
+    /// * `tolerance` The distance within which vertices are merged, see
+    ///   method `new`.
+    ///
+    /// __Returns:__
+    ///     An error if the computation of a cell failed.
+    pub fn from_container<T: ContainerBase>(container: &T, tolerance: f64) -> Result<Self, Error> {
+        let mut grid = Self::new(tolerance);
+        for result in container.cells_neighbor() {
+            let (id, position, _radius, cell) = result?;
+            grid.add_cell(id, &position, &cell.geometry());
+        }
+        Ok(grid)
+    }
+
+    /// Makes a grid from snapshots of Voronoi cells.
+    ///
+    /// __Parameters:__
+    /// * `cells` The tuples (`id`, `position`, `geometry`) of each particle.
+    /// * `tolerance` The distance within which vertices are merged, see
+    ///   method `new`.
+    pub fn from_geometry(cells: &[(i32, [f64; 3], CellGeometry)], tolerance: f64) -> Self {
+        let mut grid = Self::new(tolerance);
+        for (id, position, geometry) in cells {
+            grid.add_cell(*id, position, geometry);
+        }
+        grid
+    }
+
+    /// Adds a Voronoi cell to the grid.
+    ///
+    /// __Parameters:__
+    /// * `id` The ID of the particle.
+    /// * `position` The position of the particle, which is added to the
+    ///   vertices of the cell.
+    /// * `geometry` The geometry of the Voronoi cell of the particle.
+    pub fn add_cell(&mut self, id: i32, position: &[f64; 3], geometry: &CellGeometry) {
+        let points: Vec<usize> = geometry
+            .vertices
+            .iter()
+            .map(|v| self.add_point([v[0] + position[0], v[1] + position[1], v[2] + position[2]]))
+            .collect();
+        let mut unique = points.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        self.cells.push(GridCell {
+            particle: id,
+            volume: geometry.volume,
+            surface_area: geometry.surface_area,
+            points: unique,
+            faces: geometry
+                .faces
+                .iter()
+                .map(|face| face.iter().rev().map(|&v| points[v]).collect())
+                .collect(),
+            face_areas: geometry.face_areas(),
+            face_neighbors: geometry
+                .neighbors
+                .clone()
+                .unwrap_or_else(|| vec![UNKNOWN_NEIGHBOR; geometry.faces.len()]),
+        });
+    }
+
+    /// Returns the number of distinct points in the grid.
+    pub fn number_of_points(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns the number of Voronoi cells in the grid.
+    pub fn number_of_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Finds the index of a point, adding it to the grid if there is no point
+    /// within the tolerance of it.
+    fn add_point(&mut self, p: [f64; 3]) -> usize {
+        let size = if self.tolerance > 0.0 {
+            self.tolerance
+        } else {
+            1.0
+        };
+        let key = [
+            (p[0] / size).floor() as i64,
+            (p[1] / size).floor() as i64,
+            (p[2] / size).floor() as i64,
+        ];
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let near = [key[0] + dx, key[1] + dy, key[2] + dz];
+                    for &i in self.buckets.get(&near).into_iter().flatten() {
+                        let q = &self.points[i];
+                        let d = [p[0] - q[0], p[1] - q[1], p[2] - q[2]];
+                        if (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt() <= self.tolerance {
+                            return i;
+                        }
+                    }
+                }
+            }
+        }
+        self.points.push(p);
+        self.buckets
+            .entry(key)
+            .or_default()
+            .push(self.points.len() - 1);
+        self.points.len() - 1
+    }
+
+    /// Writes the grid in the VTK XML unstructured grid format.
+    pub fn write<W: Write>(&self, mut w: W, encoding: Encoding) -> io::Result<()> {
+        writeln!(w, r#"<?xml version="1.0"?>"#)?;
+        writeln!(
+            w,
+            r#"<VTKFile type="UnstructuredGrid" version="1.0" byte_order="LittleEndian" header_type="UInt64">"#
+        )?;
+        writeln!(w, "<UnstructuredGrid>")?;
+
+        writeln!(
+            w,
+            r#"<Piece NumberOfPoints="{}" NumberOfCells="{}">"#,
+            self.points.len(),
+            self.cells.len()
+        )?;
+        writeln!(w, "<Points>")?;
+        let points: Vec<f64> = self.points.iter().flatten().copied().collect();
+        write_array(&mut w, "Points", 3, &points, encoding)?;
+        writeln!(w, "</Points>")?;
+
+        let mut connectivity = vec![];
+        let mut offsets = vec![];
+        let mut faces = vec![];
+        let mut face_offsets = vec![];
+        for cell in &self.cells {
+            connectivity.extend(cell.points.iter().map(|&p| p as i64));
+            offsets.push(connectivity.len() as i64);
+            faces.push(cell.faces.len() as i64);
+            for face in &cell.faces {
+                faces.push(face.len() as i64);
+                faces.extend(face.iter().map(|&p| p as i64));
+            }
+            face_offsets.push(faces.len() as i64);
+        }
+        writeln!(w, "<Cells>")?;
+        write_array(&mut w, "connectivity", 1, &connectivity, encoding)?;
+        write_array(&mut w, "offsets", 1, &offsets, encoding)?;
+        write_array(
+            &mut w,
+            "types",
+            1,
+            &vec![VTK_POLYHEDRON; self.cells.len()],
+            encoding,
+        )?;
+        write_array(&mut w, "faces", 1, &faces, encoding)?;
+        write_array(&mut w, "faceoffsets", 1, &face_offsets, encoding)?;
+        writeln!(w, "</Cells>")?;
+
+        writeln!(w, r#"<CellData Scalars="Volume">"#)?;
+        let ids: Vec<i32> = self.cells.iter().map(|c| c.particle).collect();
+        write_array(&mut w, "ParticleID", 1, &ids, encoding)?;
+        let volumes: Vec<f64> = self.cells.iter().map(|c| c.volume).collect();
+        write_array(&mut w, "Volume", 1, &volumes, encoding)?;
+        let areas: Vec<f64> = self.cells.iter().map(|c| c.surface_area).collect();
+        write_array(&mut w, "SurfaceArea", 1, &areas, encoding)?;
+        let num_faces: Vec<i32> = self.cells.iter().map(|c| c.faces.len() as i32).collect();
+        write_array(&mut w, "NumberOfFaces", 1, &num_faces, encoding)?;
+        let max_faces = self.cells.iter().map(|c| c.faces.len()).max().unwrap_or(0);
+        let mut face_areas = vec![];
+        let mut face_neighbors = vec![];
+        for cell in &self.cells {
+            face_areas.extend(&cell.face_areas);
+            face_areas.resize(face_areas.len() + max_faces - cell.faces.len(), 0.0);
+            face_neighbors.extend(&cell.face_neighbors);
+            face_neighbors.resize(
+                face_neighbors.len() + max_faces - cell.faces.len(),
+                UNKNOWN_NEIGHBOR,
+            );
+        }
+        let components = max_faces.max(1);
+        write_array(&mut w, "FaceArea", components, &face_areas, encoding)?;
+        write_array(
+            &mut w,
+            "FaceNeighbor",
+            components,
+            &face_neighbors,
+            encoding,
+        )?;
+        writeln!(w, "</CellData>")?;
+
+        writeln!(w, "</Piece>")?;
+        writeln!(w, "</UnstructuredGrid>")?;
+        writeln!(w, "</VTKFile>")?;
+        w.flush()
+    }
+}
+
+/// The numeric types of the data arrays.
+trait DataValue: Copy + Display {
+    const TYPE: &'static str;
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! impl_data_value {
+    ($t:ty, $name:expr) => {
+        impl DataValue for $t {
+            const TYPE: &'static str = $name;
+            fn extend_le_bytes(self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+    };
+}
+
+impl_data_value!(u8, "UInt8");
+impl_data_value!(i32, "Int32");
+impl_data_value!(i64, "Int64");
+impl_data_value!(f64, "Float64");
+
+fn write_array<W: Write, T: DataValue>(
+    w: &mut W,
+    name: &str,
+    components: usize,
+    data: &[T],
+    encoding: Encoding,
+) -> io::Result<()> {
+    let format = match encoding {
+        Encoding::Ascii => "ascii",
+        Encoding::Binary => "binary",
+    };
+    writeln!(
+        w,
+        r#"<DataArray type="{}" Name="{}" NumberOfComponents="{}" NumberOfTuples="{}" format="{}">"#,
+        T::TYPE,
+        name,
+        components,
+        data.len() / components,
+        format
+    )?;
+    match encoding {
+        Encoding::Ascii => {
+            for row in data.chunks(components.max(1) * 8) {
+                let mut sep = "";
+                for x in row {
+                    write!(w, "{}{}", sep, x)?;
+                    sep = " ";
+                }
+                writeln!(w)?;
+            }
+        }
+        Encoding::Binary => {
+            // Uncompressed binary data is preceded by its length in bytes,
+            // and both are encoded together.
+            let len = std::mem::size_of_val(data);
+            let mut bytes = Vec::with_capacity(8 + len);
+            bytes.extend_from_slice(&(len as u64).to_le_bytes());
+            for &x in data {
+                x.extend_le_bytes(&mut bytes);
+            }
+            writeln!(w, "{}", base64(&bytes))?;
+        }
+    }
+    writeln!(w, "</DataArray>")
+}
+
+/// Encodes binary data in base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::{Container, VoronoiCell};
+    assert!(base64(b"").is_empty());
+    assert!(base64(b"M") == "TQ==");
+    assert!(base64(b"Ma") == "TWE=");
+    assert!(base64(b"Man") == "TWFu");
+    assert!(base64(&[0xff; 4]) == "/////w==");
+
+    let mut con = Container::new(&[0.0; 3], &[2.0; 3], &[1, 1, 1], &[false; 3], 8).unwrap();
+    con.put(7, &[0.5, 1.0, 1.0]).unwrap();
+    con.put(8, &[1.5, 1.0, 1.0]).unwrap();
+    let grid = UnstructuredGrid::from_container(&con, 1e-9).unwrap();
+    assert!(grid.number_of_cells() == 2);
+    // Two boxes which share a face.
+    assert!(grid.number_of_points() == 12);
+    let mut ascii = vec![];
+    grid.write(&mut ascii, Encoding::Ascii).unwrap();
+    let ascii = String::from_utf8(ascii).unwrap();
+    assert!(ascii.contains(r#"<Piece NumberOfPoints="12" NumberOfCells="2">"#));
+    assert!(ascii.contains("format=\"ascii\">\n42 42\n"));
+    assert!(ascii.contains("Name=\"ParticleID\""));
+    assert!(ascii.contains("Name=\"FaceNeighbor\" NumberOfComponents=\"6\" NumberOfTuples=\"2\""));
+    assert!(ascii.contains(
+        "Name=\"Volume\" NumberOfComponents=\"1\" NumberOfTuples=\"2\" format=\"ascii\">\n4 4\n"
+    ));
+
+    let mut binary = vec![];
+    grid.write(&mut binary, Encoding::Binary).unwrap();
+    let binary = String::from_utf8(binary).unwrap();
+    assert!(!binary.contains("format=\"ascii\""));
+    // The cell types are 8 bytes of length and 2 bytes of data.
+    assert!(binary.contains(&format!(
+        "NumberOfTuples=\"2\" format=\"binary\">\n{}\n",
+        base64(&[2, 0, 0, 0, 0, 0, 0, 0, 42, 42])
+    )));
+
+    let cube = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]).geometry();
+    let grid = UnstructuredGrid::from_geometry(
+        &[(0, [0.0; 3], cube.clone()), (1, [3.0, 0.0, 0.0], cube)],
+        0.0,
+    );
+    assert!(grid.number_of_cells() == 2);
+    assert!(grid.number_of_points() == 16);
+    // Cells without neighbor information are not confused with particle 0.
+    let mut ascii = vec![];
+    grid.write(&mut ascii, Encoding::Ascii).unwrap();
+    let ascii = String::from_utf8(ascii).unwrap();
+    let unknown = vec![UNKNOWN_NEIGHBOR.to_string(); 8].join(" ");
+    assert!(ascii.contains(&unknown));
+}
+
+/// Reads back a data array from a grid in the ASCII format, as its number of
+/// components and its values.
+#[cfg(test)]
+fn read_ascii_array(text: &str, name: &str) -> (usize, Vec<f64>) {
+    let start = text.find(&format!("Name=\"{}\"", name)).unwrap();
+    let text = &text[start..];
+    let components = text.split('"').nth(3).unwrap().parse().unwrap();
+    let data = &text[text.find('>').unwrap() + 1..text.find("</DataArray>").unwrap()];
+    let values = data
+        .split_whitespace()
+        .map(|x| x.parse().unwrap())
+        .collect();
+    (components, values)
+}
+
+/// Check that the face data of each cell is stored with that cell.
+#[test]
+fn face_data() {
+    use crate::{VoronoiCell, VoronoiCellNeighbor};
+    let cube = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]).geometry();
+    let mut cut = VoronoiCellNeighbor::init(&[-1.0; 3], &[1.0; 3]);
+    assert!(cut.nplane(&[1.0, 1.0, 1.0], 5).unwrap());
+    let cut = cut.geometry();
+    let grid = UnstructuredGrid::from_geometry(
+        &[
+            (0, [0.0; 3], cube.clone()),
+            (1, [3.0, 0.0, 0.0], cut.clone()),
+        ],
+        0.0,
+    );
+    let mut ascii = vec![];
+    grid.write(&mut ascii, Encoding::Ascii).unwrap();
+    let ascii = String::from_utf8(ascii).unwrap();
+    assert!(!ascii.contains("FieldData"));
+    let cell_data = &ascii[ascii.find("<CellData").unwrap()..];
+
+    let (components, areas) = read_ascii_array(cell_data, "FaceArea");
+    assert!(components == 7);
+    assert!(areas.len() == 2 * components);
+    let (_, neighbors) = read_ascii_array(cell_data, "FaceNeighbor");
+    assert!(neighbors.len() == 2 * components);
+    for (i, geometry) in [cube, cut].iter().enumerate() {
+        let row = i * components..(i + 1) * components;
+        let n = geometry.faces.len();
+        for (a, b) in areas[row.clone()].iter().zip(geometry.face_areas()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert!(areas[row.clone()][n..].iter().all(|&a| a == 0.0));
+        let expected: Vec<f64> = match &geometry.neighbors {
+            Some(neighbors) => neighbors.iter().map(|&x| x as f64).collect(),
+            None => vec![UNKNOWN_NEIGHBOR as f64; n],
+        };
+        assert!(neighbors[row.clone()][..n] == expected[..]);
+        assert!(neighbors[row][n..]
+            .iter()
+            .all(|&x| x == UNKNOWN_NEIGHBOR as f64));
+    }
+    assert!(neighbors[components..].contains(&5.0));
+}
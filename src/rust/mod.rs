@@ -1,5 +1,23 @@
 //! This directory contains the Rust language interface.
 
+pub(crate) mod c_file;
+pub mod cell_geometry;
+pub mod cell_stats;
+pub mod container;
+pub mod container_base;
+pub mod container_periodic;
+pub mod container_periodic_poly;
+pub mod container_poly;
+pub mod custom_format;
+pub mod error;
+pub mod io;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub(crate) mod polyhedron;
+pub mod positioned_cell;
+pub mod unit_cell;
 pub mod voronoi_cell;
 pub mod voronoi_cell_base;
 pub mod voronoi_cell_neighbor;
+pub mod voronoi_index;
+pub mod wall;
@@ -0,0 +1,211 @@
+use crate::rust::container_base::{ContainerBase, ContainerCell, ContainerParallelFFI};
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell::VoronoiCell;
+use crate::rust::voronoi_cell_neighbor::VoronoiCellNeighbor;
+use rayon::prelude::*;
+
+/// Private trait. Selects which variant of Voronoi cell is computed by the
+/// parallel methods of a container.
+pub trait ParallelCell: ContainerCell + Send {
+    fn compute_particle<T: ContainerParallelFFI>(
+        container: &T,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+    ) -> Result<Option<Self>, Error>;
+}
+
+impl ParallelCell for VoronoiCell {
+    fn compute_particle<T: ContainerParallelFFI>(
+        container: &T,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+    ) -> Result<Option<Self>, Error> {
+        let mut error = None;
+        let cell = container.compute_particle(tc, ijk, q, id, xyz, r, &mut error);
+        if cell.is_null() {
+            Error::check(None, error)
+        } else {
+            Ok(Some(VoronoiCell(cell)))
+        }
+    }
+}
+
+impl ParallelCell for VoronoiCellNeighbor {
+    fn compute_particle<T: ContainerParallelFFI>(
+        container: &T,
+        tc: *mut std::ffi::c_void,
+        ijk: i32,
+        q: i32,
+        id: &mut i32,
+        xyz: &mut [f64; 3],
+        r: &mut f64,
+    ) -> Result<Option<Self>, Error> {
+        let mut error = None;
+        let cell = container.compute_particle_neighbor(tc, ijk, q, id, xyz, r, &mut error);
+        if cell.is_null() {
+            Error::check(None, error)
+        } else {
+            Ok(Some(VoronoiCellNeighbor(cell)))
+        }
+    }
+}
+
+/// The (`id`, `position`, `radius`, `cell`) of a particle, the same as the
+/// items of the iterator `ContainerBase::cells`.
+pub type ParticleCell<C> = (i32, [f64; 3], f64, C);
+
+/// Shares a container between threads. This is safe because the parallel
+/// computation only reads from the container, and each thread computes the
+/// cells using its own scratch space.
+struct Shared<'a, T>(&'a T);
+
+unsafe impl<T> Sync for Shared<'_, T> {}
+
+/// The scratch space for computing Voronoi cells on one thread.
+struct ThreadCompute<'a, T: ContainerParallelFFI> {
+    container: &'a T,
+    tc: *mut std::ffi::c_void,
+}
+
+impl<'a, T: ContainerParallelFFI> ThreadCompute<'a, T> {
+    fn new(container: &'a T) -> Self {
+        Self {
+            container,
+            tc: container.new_thread_compute(),
+        }
+    }
+}
+
+impl<'a, T: ContainerParallelFFI> Drop for ThreadCompute<'a, T> {
+    fn drop(&mut self) {
+        self.container.delete_thread_compute(self.tc);
+    }
+}
+
+/// Methods for computing the Voronoi cells of a container on many threads at
+/// once, using the thread pool of the `rayon` crate.
+///
+/// The computational blocks of the container are divided between the
+/// threads, and each thread computes the cells of the particles in its blocks.
+/// Walls which are implemented in Rust are called from all of the threads.
+pub trait ContainerParallel: ContainerBase + ContainerParallelFFI {
+    /// Computes the Voronoi cell of every particle in the container, in
+    /// parallel, and applies a function to each of them.
+    ///
+    /// __Parameters:__
+    /// * `f` A function of (`id`, `position`, `radius`, `cell`), the same as
+    ///   the items of the iterator `ContainerBase::cells`. The cell type is
+    ///   either `VoronoiCell` or `VoronoiCellNeighbor`.
+    ///
+    /// __Returns:__
+    ///     The results of the function, in the same order as the iterator
+    ///     `ContainerBase::cells`, or the first error which was encountered.
+    ///     Particles whose cell could not be computed are skipped.
+    fn par_map_cells<C, R, F>(&self, f: F) -> Result<Vec<R>, Error>
+    where
+        Self: Sized,
+        C: ParallelCell,
+        R: Send,
+        F: Fn(i32, [f64; 3], f64, C) -> R + Sync + Send,
+    {
+        let shared = Shared(self);
+        let shared = &shared;
+        let blocks: Result<Vec<Vec<R>>, Error> = (0..self.number_of_blocks())
+            .into_par_iter()
+            .map_init(
+                || ThreadCompute::new(shared.0),
+                |tc, ijk| {
+                    let container = shared.0;
+                    let mut results = vec![];
+                    for q in 0..container.block_size(ijk) {
+                        let mut id = 0;
+                        let mut xyz = [f64::NAN; 3];
+                        let mut r = f64::NAN;
+                        let cell = C::compute_particle(
+                            container, tc.tc, ijk, q, &mut id, &mut xyz, &mut r,
+                        )?;
+                        if let Some(cell) = cell {
+                            results.push(f(id, xyz, r, cell));
+                        }
+                    }
+                    Ok(results)
+                },
+            )
+            .collect();
+        Ok(blocks?.into_iter().flatten().collect())
+    }
+
+    /// Computes the Voronoi cell of every particle in the container, in
+    /// parallel.
+    ///
+    /// Returns a list of the tuples (`id`, `position`, `radius`, `cell`) of
+    /// each particle, the same as the iterator `ContainerBase::cells`.
+    fn par_cells(&self) -> Result<Vec<ParticleCell<VoronoiCell>>, Error>
+    where
+        Self: Sized,
+    {
+        self.par_map_cells(|id, xyz, r, cell| (id, xyz, r, cell))
+    }
+
+    /// Computes the Voronoi cell of every particle in the container with
+    /// neighbor information, in parallel.
+    ///
+    /// Returns a list of the tuples (`id`, `position`, `radius`, `cell`) of
+    /// each particle, the same as the iterator `ContainerBase::cells_neighbor`.
+    fn par_cells_neighbor(&self) -> Result<Vec<ParticleCell<VoronoiCellNeighbor>>, Error>
+    where
+        Self: Sized,
+    {
+        self.par_map_cells(|id, xyz, r, cell| (id, xyz, r, cell))
+    }
+}
+
+impl<T: ContainerBase + ContainerParallelFFI> ContainerParallel for T {}
+
+/// Check that the parallel computation finds the same cells as the serial one.
+#[test]
+fn parallel_matches_serial() {
+    use crate::{ContainerPoly, VoronoiCellBase, WallSphere};
+    let mut con = ContainerPoly::new(&[-1.0; 3], &[1.0; 3], &[6, 6, 6], &[false; 3], 8).unwrap();
+    con.add_wall(WallSphere::new(&[0.0; 3], 0.9, -7)).unwrap();
+    // A deterministic pseudo-random sequence.
+    let mut seed = 12345_u64;
+    let mut random = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 11) as f64 / (1_u64 << 53) as f64
+    };
+    for id in 0..2000 {
+        let xyz = [
+            2.0 * random() - 1.0,
+            2.0 * random() - 1.0,
+            2.0 * random() - 1.0,
+        ];
+        con.put(id, &xyz, 0.01 + 0.02 * random()).unwrap();
+    }
+    let serial: Vec<_> = con
+        .cells_neighbor()
+        .map(Result::unwrap)
+        .map(|(id, xyz, r, cell)| (id, xyz, r, cell.volume(), cell.neighbors()))
+        .collect();
+    let parallel = con
+        .par_map_cells(|id, xyz, r, cell: VoronoiCellNeighbor| {
+            (id, xyz, r, cell.volume(), cell.neighbors())
+        })
+        .unwrap();
+    assert!(serial.len() > 1000);
+    assert!(serial == parallel);
+    let cells = con.par_cells().unwrap();
+    assert!(cells.len() == serial.len());
+    let total: f64 = cells.iter().map(|(_, _, _, cell)| cell.volume()).sum();
+    assert!(total < 8.0);
+}
@@ -0,0 +1,330 @@
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell_base::VoronoiCellBase;
+use crate::rust::voronoi_cell_neighbor::VoronoiCellNeighbor;
+use std::collections::HashMap;
+
+/// The edge table of a convex polyhedron, in the layout which the C++ function
+/// "voronoicell_base::init_polyhedron_base" expects.
+pub(crate) struct Polyhedron {
+    /// The vertex coordinates, three per vertex.
+    pub vertices: Vec<f64>,
+
+    /// The order of each vertex.
+    pub orders: Vec<i32>,
+
+    /// The neighbors of each vertex, listed consecutively.
+    pub edges: Vec<i32>,
+
+    /// The face ID of the face which is traced from each edge in the list
+    /// `edges`, listed consecutively.
+    pub neighbors: Vec<i32>,
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::InvalidPolyhedron(msg.to_string())
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Calculates the normal vector of a polygon using Newell's method. The
+/// normal vector follows the right hand rule, and its length is twice the area
+/// of the polygon.
+fn newell_normal(vertices: &[[f64; 3]], face: &[usize]) -> [f64; 3] {
+    let mut n = [0.0; 3];
+    for (i, &a) in face.iter().enumerate() {
+        let (a, b) = (&vertices[a], &vertices[face[(i + 1) % face.len()]]);
+        n[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        n[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        n[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    n
+}
+
+/// Checks that the given faces form the closed surface of a convex
+/// polyhedron, and converts it into an edge table.
+///
+/// The faces may be ordered either clockwise or counterclockwise as seen from
+/// outside of the polyhedron, as long as all of the faces are ordered the same
+/// way.
+///
+/// __Parameters:__
+/// * `vertices` The vertex coordinates.
+/// * `faces` The vertices of each face, as indices into the vertices list.
+/// * `neighbors` The face ID of each face, or None to set them all to zero.
+pub(crate) fn build(
+    vertices: &[[f64; 3]],
+    faces: &[Vec<usize>],
+    neighbors: Option<&[i32]>,
+) -> Result<Polyhedron, Error> {
+    let num_vertices = vertices.len();
+    if num_vertices < 4 || faces.len() < 4 {
+        return Err(invalid("Too few vertices or faces"));
+    }
+    if vertices.iter().flatten().any(|x| !x.is_finite()) {
+        return Err(invalid("Vertex coordinates are not finite"));
+    }
+    if neighbors.is_some_and(|n| n.len() != faces.len()) {
+        return Err(invalid("Number of face IDs does not match number of faces"));
+    }
+
+    // Map each directed edge to the face which contains it.
+    let mut directed_edges = HashMap::new();
+    for (f, face) in faces.iter().enumerate() {
+        if face.len() < 3 {
+            return Err(invalid("Face has fewer than three vertices"));
+        }
+        for (i, &a) in face.iter().enumerate() {
+            if a >= num_vertices {
+                return Err(invalid("Vertex index is out of range"));
+            }
+            if face[..i].contains(&a) {
+                return Err(invalid("Face contains a vertex more than once"));
+            }
+            let b = face[(i + 1) % face.len()];
+            if directed_edges.insert((a, b), f).is_some() {
+                return Err(invalid(
+                    "Edge is shared by faces with inconsistent orientation",
+                ));
+            }
+        }
+    }
+    if directed_edges
+        .keys()
+        .any(|&(a, b)| !directed_edges.contains_key(&(b, a)))
+    {
+        return Err(invalid("Surface is not closed"));
+    }
+    let num_edges = directed_edges.len() / 2;
+    if num_vertices + faces.len() != num_edges + 2 {
+        return Err(invalid("Surface is not topologically a sphere"));
+    }
+
+    // Orient the faces clockwise as seen from outside, which is the order that
+    // the Voronoi cells use. The signed volume is positive for faces which are
+    // ordered counterclockwise.
+    let volume: f64 = faces
+        .iter()
+        .map(|face| dot(&vertices[face[0]], &newell_normal(vertices, face)))
+        .sum();
+    let scale = vertices
+        .iter()
+        .flat_map(|v| v.iter())
+        .fold(0.0f64, |m, x| m.max(x.abs()))
+        .max(f64::MIN_POSITIVE);
+    if volume.abs() <= 1e-12 * scale.powi(3) {
+        return Err(invalid("Polyhedron has no volume"));
+    }
+    let reverse = volume > 0.0;
+    let faces: Vec<Vec<usize>> = faces
+        .iter()
+        .map(|face| {
+            let mut face = face.clone();
+            if reverse {
+                face.reverse();
+            }
+            face
+        })
+        .collect();
+
+    // Check that every face is planar, and that no vertex is outside of the
+    // plane of any face.
+    let tolerance = 1e-9 * scale;
+    for face in &faces {
+        let n = newell_normal(vertices, face);
+        let len = dot(&n, &n).sqrt();
+        if len == 0.0 {
+            return Err(invalid("Face has no area"));
+        }
+        let n = [-n[0] / len, -n[1] / len, -n[2] / len];
+        let origin = &vertices[face[0]];
+        for (v, x) in vertices.iter().enumerate() {
+            let height = dot(&n, &sub(x, origin));
+            if height > tolerance || (face.contains(&v) && height < -tolerance) {
+                return Err(invalid("Polyhedron is not convex"));
+            }
+        }
+    }
+
+    // The face after the edge from vertex i to vertex k continues with the
+    // edge from vertex k to the vertex after i, in the cyclic order of the
+    // neighbors of vertex k.
+    let mut successors = vec![HashMap::new(); num_vertices];
+    for face in &faces {
+        for (i, &k) in face.iter().enumerate() {
+            let prev = face[(i + face.len() - 1) % face.len()];
+            successors[k].insert(prev, face[(i + 1) % face.len()]);
+        }
+    }
+    let mut polyhedron = Polyhedron {
+        vertices: vertices.iter().flatten().copied().collect(),
+        orders: Vec::with_capacity(num_vertices),
+        edges: Vec::with_capacity(2 * num_edges),
+        neighbors: Vec::with_capacity(2 * num_edges),
+    };
+    for (k, succ) in successors.iter().enumerate() {
+        let start = match succ.keys().min() {
+            Some(&start) if succ.len() >= 3 => start,
+            _ => return Err(invalid("Vertex has fewer than three edges")),
+        };
+        let mut i = start;
+        let mut order = 0;
+        loop {
+            polyhedron.edges.push(i as i32);
+            let f = directed_edges[&(if reverse { (i, k) } else { (k, i) })];
+            polyhedron.neighbors.push(neighbors.map_or(0, |n| n[f]));
+            order += 1;
+            i = succ[&i];
+            if i == start {
+                break;
+            }
+        }
+        if order != succ.len() {
+            return Err(invalid("Vertex is shared by separate parts of the surface"));
+        }
+        polyhedron.orders.push(succ.len() as i32);
+    }
+    Ok(polyhedron)
+}
+
+/// Calculates the planes of the faces of the convex hull of the given points,
+/// in the form which the method `VoronoiCell::init_convex_polyhedron` takes.
+///
+/// Relative to a point inside of the hull, the hull is the polar dual of the
+/// intersection of the half-spaces `v · p <= 1` for every point `p`, so each
+/// vertex `w` of that intersection is the half-space `v · w <= 1` of a face of
+/// the hull.
+pub(crate) fn hull_planes(points: &[[f64; 3]]) -> Result<Vec<([f64; 3], f64)>, Error> {
+    if points.len() < 4 {
+        return Err(invalid("Too few points"));
+    }
+    if points.iter().flatten().any(|x| !x.is_finite()) {
+        return Err(invalid("Point coordinates are not finite"));
+    }
+    let n = points.len() as f64;
+    let mut center = [0.0; 3];
+    for p in points {
+        for i in 0..3 {
+            center[i] += p[i] / n;
+        }
+    }
+    let scale = points
+        .iter()
+        .map(|p| {
+            let d = sub(p, &center);
+            dot(&d, &d).sqrt()
+        })
+        .fold(0.0f64, f64::max);
+    let dual_planes: Vec<([f64; 3], f64)> = points
+        .iter()
+        .map(|p| {
+            let d = sub(p, &center);
+            ([d[0] / scale, d[1] / scale, d[2] / scale], 1.0)
+        })
+        .filter(|(d, _)| dot(d, d) > 1e-20)
+        .collect();
+    let dual = match VoronoiCellNeighbor::init_convex_polyhedron(&dual_planes) {
+        Err(Error::InvalidPolyhedron(_)) => return Err(invalid("Points have no volume")),
+        result => result?,
+    };
+    Ok(dual
+        .vertices()
+        .into_iter()
+        .map(|w| (w, scale + dot(&center, &w)))
+        .collect())
+}
+
+/// Check that invalid polyhedra are rejected.
+#[test]
+fn validation() {
+    let cube: Vec<[f64; 3]> = (0..8)
+        .map(|i| [(i & 1) as f64, (i >> 1 & 1) as f64, (i >> 2 & 1) as f64])
+        .collect();
+    let faces = vec![
+        vec![0, 2, 3, 1],
+        vec![4, 5, 7, 6],
+        vec![0, 1, 5, 4],
+        vec![2, 6, 7, 3],
+        vec![0, 4, 6, 2],
+        vec![1, 3, 7, 5],
+    ];
+    let p = build(&cube, &faces, Some(&[1, 2, 3, 4, 5, 6])).unwrap();
+    assert!(p.orders == vec![3; 8]);
+    assert!(p.edges.len() == 24);
+    assert!(p.neighbors.iter().filter(|&&n| n == 1).count() == 4);
+    let is_invalid = |vertices: &[[f64; 3]], faces: &[Vec<usize>]| {
+        matches!(
+            build(vertices, faces, None),
+            Err(Error::InvalidPolyhedron(_))
+        )
+    };
+    // Open surface.
+    assert!(is_invalid(&cube, &faces[1..]));
+    // Inconsistent orientation.
+    let mut f = faces.clone();
+    f[0].reverse();
+    assert!(is_invalid(&cube, &f));
+    // Vertex index out of range.
+    let mut f = faces.clone();
+    f[0][0] = 8;
+    assert!(is_invalid(&cube, &f));
+    // Non-convex: push one corner inwards.
+    let mut v = cube.clone();
+    v[7] = [0.6, 0.6, 0.6];
+    assert!(is_invalid(&v, &faces));
+    // Non-planar face.
+    let mut v = cube.clone();
+    v[7] = [1.0, 1.0, 1.2];
+    assert!(is_invalid(&v, &faces));
+    // Wrong number of face IDs.
+    assert!(build(&cube, &faces, Some(&[1, 2])).is_err());
+    assert!(is_invalid(&cube[..3], &faces));
+}
+
+/// Reconstruct polyhedra which have more vertices of order four than fit into
+/// the initial memory of a Voronoi cell.
+#[test]
+fn reconstruct_high_order() {
+    use crate::VoronoiCell;
+    use std::convert::TryFrom;
+    let cyclic = |v: [f64; 3]| vec![v, [v[1], v[2], v[0]], [v[2], v[0], v[1]]];
+    let signs = |v: [f64; 3]| -> Vec<[f64; 3]> {
+        (0..8)
+            .map(|i| {
+                let s = |k: usize| if i >> k & 1 == 1 { -1.0 } else { 1.0 };
+                [s(0) * v[0], s(1) * v[1], s(2) * v[2]]
+            })
+            .collect()
+    };
+    let mut cuboctahedron: Vec<[f64; 3]> = cyclic([1.0, 1.0, 0.0])
+        .into_iter()
+        .flat_map(signs)
+        .collect();
+    cuboctahedron.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    cuboctahedron.dedup();
+    let phi = (1.0 + 5.0f64.sqrt()) / 2.0;
+    let mut icosidodecahedron: Vec<[f64; 3]> = cyclic([0.0, 0.0, phi])
+        .into_iter()
+        .chain(cyclic([0.5, phi / 2.0, phi * phi / 2.0]))
+        .flat_map(signs)
+        .collect();
+    icosidodecahedron.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    icosidodecahedron.dedup();
+    for (points, num_vertices) in [(cuboctahedron, 12), (icosidodecahedron, 30)] {
+        assert!(points.len() == num_vertices);
+        let hull = VoronoiCell::init_convex_hull(&points).unwrap();
+        let g = hull.geometry();
+        assert!(g.vertices.len() == num_vertices);
+        assert!(hull.vertex_orders() == vec![4; num_vertices]);
+        let x = VoronoiCellNeighbor::try_from(&g).unwrap();
+        assert!(x.vertex_orders() == vec![4; num_vertices]);
+        assert!((x.volume() - g.volume).abs() < 1e-12 * g.volume);
+        let x = VoronoiCell::init_polyhedron(&g.vertices, &g.faces).unwrap();
+        assert!((x.surface_area() - g.surface_area).abs() < 1e-12 * g.surface_area);
+    }
+}
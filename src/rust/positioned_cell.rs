@@ -0,0 +1,118 @@
+use crate::rust::voronoi_cell_base::VoronoiCellBase;
+
+/// A Voronoi cell together with the position of its particle.
+///
+/// The vertices of a Voronoi cell are stored relative to the position of its
+/// particle. This wrapper answers geometric queries in the global coordinate
+/// system instead. Queries which do not depend on the position, such as the
+/// volume, are available through the method `cell`.
+#[derive(Clone)]
+pub struct PositionedCell<C: VoronoiCellBase> {
+    cell: C,
+    position: [f64; 3],
+}
+
+impl<C: VoronoiCellBase> PositionedCell<C> {
+    /// __Parameters:__
+    /// * `cell` A Voronoi cell, in the local coordinate system of a particle.
+    /// * `position` The position of the particle in the global coordinate
+    ///   system.
+    pub fn new(cell: C, position: &[f64; 3]) -> Self {
+        Self {
+            cell,
+            position: *position,
+        }
+    }
+
+    /// Returns the position of the particle.
+    pub fn position(&self) -> [f64; 3] {
+        self.position
+    }
+
+    /// Returns the Voronoi cell, in the local coordinate system.
+    pub fn cell(&self) -> &C {
+        &self.cell
+    }
+
+    /// Returns the Voronoi cell, in the local coordinate system.
+    pub fn cell_mut(&mut self) -> &mut C {
+        &mut self.cell
+    }
+
+    /// Returns the Voronoi cell, discarding the position of the particle.
+    pub fn into_cell(self) -> C {
+        self.cell
+    }
+
+    /// Returns a vector of the vertex coordinates in the global coordinate
+    /// system.
+    pub fn vertices(&self) -> Vec<[f64; 3]> {
+        self.cell.vertices_at(&self.position)
+    }
+
+    /// Calculates the centroid of the Voronoi cell in the global coordinate
+    /// system.
+    pub fn centroid(&self) -> [f64; 3] {
+        let c = self.cell.centroid();
+        let p = &self.position;
+        [c[0] + p[0], c[1] + p[1], c[2] + p[2]]
+    }
+
+    /// Calculates the outward unit normal vector of each face of the Voronoi
+    /// cell, in the same order as the method `VoronoiCellBase::face_vertices`.
+    pub fn normals(&self) -> Vec<[f64; 3]> {
+        self.cell.normals()
+    }
+
+    /// Calculates the plane of each face of the Voronoi cell in the global
+    /// coordinate system, in the same order as the method
+    /// `VoronoiCellBase::face_vertices`.
+    ///
+    /// Returns a list of the pairs (`normal`, `offset`), where `normal` is the
+    /// outward unit normal vector of the face and the face lies on the plane
+    /// of points `x` which satisfy `x · normal = offset`. The cell is the set
+    /// of points which satisfy `x · normal <= offset` for every face.
+    pub fn face_planes(&self) -> Vec<([f64; 3], f64)> {
+        let vertices = self.vertices();
+        self.cell
+            .normals()
+            .into_iter()
+            .zip(self.cell.face_vertices())
+            .map(|(n, face)| {
+                let v = vertices[face[0]];
+                (n, n[0] * v[0] + n[1] * v[1] + n[2] * v[2])
+            })
+            .collect()
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::VoronoiCell;
+    let cell = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let mut x = PositionedCell::new(cell, &[10.0, 20.0, 30.0]);
+    assert!(x.position() == [10.0, 20.0, 30.0]);
+    assert!(x.centroid() == [10.0, 20.0, 30.0]);
+    assert!(x.cell().volume() == 8.0);
+    let vertices = x.vertices();
+    assert!(vertices.len() == 8);
+    assert!(vertices.contains(&[11.0, 21.0, 31.0]));
+    assert!(vertices.contains(&[9.0, 19.0, 29.0]));
+    assert!(x.cell().vertices_at(&[10.0, 20.0, 30.0]) == vertices);
+    assert!(x.normals().len() == 6);
+    let planes = x.face_planes();
+    assert!(planes.len() == 6);
+    assert!(planes.contains(&([1.0, 0.0, 0.0], 11.0)));
+    assert!(planes.contains(&([0.0, 0.0, -1.0], -29.0)));
+    for (n, d) in &planes {
+        let c = x.centroid();
+        assert!(n[0] * c[0] + n[1] * c[1] + n[2] * c[2] < *d);
+    }
+    x.cell_mut()
+        .cut_halfspace(&[1.0, 0.0, 0.0], 0.0, None)
+        .unwrap();
+    assert!(x.centroid() == [9.5, 20.0, 30.0]);
+    assert!(x.face_planes().contains(&([1.0, 0.0, 0.0], 10.0)));
+    assert!(x.into_cell().volume() == 4.0);
+}
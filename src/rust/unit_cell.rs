@@ -0,0 +1,208 @@
+use crate::rust::c_file::write_via_c_file;
+use crate::rust::container_periodic::check_lattice;
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell::VoronoiCell;
+use cpp::cpp;
+use std::io::{self, Write};
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+}}
+
+/// The unit Voronoi cell of a three-dimensional periodic lattice.
+///
+/// The lattice is defined by the three periodicity vectors (bx,0,0),
+/// (bxy,by,0), and (bxz,byz,bz). The unit Voronoi cell, also known as the
+/// Wigner-Seitz cell, is the Voronoi cell of a single lattice point with
+/// respect to all of the other lattice points. It is computed by repeatedly
+/// cutting a large box by shells of periodic images.
+pub struct UnitCell {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl UnitCell {
+    /// Computes the unit Voronoi cell of the given lattice.
+    ///
+    /// __Parameters:__
+    /// * `lattice` The three periodicity vectors (bx,0,0), (bxy,by,0), and
+    ///   (bxz,byz,bz).
+    ///
+    /// __Returns:__
+    ///     An `InvalidLattice` error if the periodicity vectors are not of the
+    ///     required form, or are not finite, or if `bx`, `by`, or `bz` is not
+    ///     positive. Also returns an error if the unit Voronoi cell could not
+    ///     be computed.
+    pub fn new(lattice: &[[f64; 3]; 3]) -> Result<Self, Error> {
+        check_lattice(lattice)?;
+        let b = [
+            lattice[0][0],
+            lattice[1][0],
+            lattice[1][1],
+            lattice[2][0],
+            lattice[2][1],
+            lattice[2][2],
+        ];
+        let b = &b;
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [b as "double*", e as "void*"] -> *mut std::ffi::c_void as "unitcell*" {
+            unitcell *u = nullptr;
+            voroxx_try(e, [&] {
+                u = new unitcell(b[0], b[1], b[2], b[3], b[4], b[5]);
+            });
+            return u;
+        });
+        Error::check(Self { ptr }, error)
+    }
+
+    /// Returns a copy of the unit Voronoi cell, centered on the origin.
+    pub fn cell(&self) -> VoronoiCell {
+        let ptr = self.ptr;
+        VoronoiCell(
+            cpp!(unsafe [ptr as "unitcell*"] -> *mut std::ffi::c_void as "voronoicell*" {
+                voronoicell* x = new voronoicell;
+                *x = ptr->unit_voro;
+                return x;
+            }),
+        )
+    }
+
+    /// Tests to see if a shifted copy of the periodic domain intersects the
+    /// unit Voronoi cell, when the cell is centered in the middle of the
+    /// primary domain.
+    ///
+    /// __Parameters:__
+    /// * `image` The periodic image (i, j, k) to test, with (0, 0, 0)
+    ///   corresponding to the primary domain.
+    ///
+    /// __Returns:__
+    ///     The volume of the overlap between the unit Voronoi cell and the
+    ///     periodic image, or None if the image does not intersect the cell.
+    pub fn intersects_image(&self, image: &[i32; 3]) -> Option<f64> {
+        let ptr = self.ptr;
+        let mut vol = f64::NAN;
+        let vol_ref = &mut vol;
+        let intersects = cpp!(unsafe [ptr as "unitcell*", image as "int*",
+                vol_ref as "double*"] -> bool as "bool" {
+            // Voro++ reports the overlap as a fraction of the cell volume.
+            bool intersects = ptr->intersects_image(image[0], image[1], image[2], *vol_ref);
+            *vol_ref *= ptr->unit_voro.volume();
+            return intersects;
+        });
+        if intersects {
+            Some(vol)
+        } else {
+            None
+        }
+    }
+
+    /// Computes the list of periodic images which intersect the unit Voronoi
+    /// cell, when the cell is centered in the middle of the primary domain.
+    ///
+    /// Returns a list of the periodic images (i, j, k), each paired with the
+    /// volume of the overlap between the unit Voronoi cell and that image.
+    /// The overlap volumes sum to the volume of the cell.
+    pub fn images(&self) -> Vec<([i32; 3], f64)> {
+        let ptr = self.ptr;
+        let mut retval = vec![];
+        let ptr_retval = &mut retval;
+        cpp!(unsafe [ptr as "unitcell*", ptr_retval as "void*"] {
+            std::vector<int> vi;
+            std::vector<double> vd;
+            ptr->images(vi, vd);
+            int *image_data = vi.data();
+            double *vol_data = vd.data();
+            int len = vd.size();
+            // Voro++ reports each overlap as a fraction of the cell volume.
+            double cell_volume = ptr->unit_voro.volume();
+            rust!(_unused_name [image_data: *const [i32; 3] as "int*",
+                                vol_data: *const f64 as "double*",
+                                len: i32 as "int",
+                                cell_volume: f64 as "double",
+                                ptr_retval: &mut Vec<([i32; 3], f64)> as "void*"] {
+                for i in 0..len as usize {
+                    ptr_retval.push((*image_data.add(i), *vol_data.add(i) * cell_volume));
+                }
+            });
+        });
+        retval
+    }
+
+    /// Writes an outline of the primary domain in the gnuplot format.
+    pub fn draw_domain_gnuplot(&self, w: &mut dyn Write) -> io::Result<()> {
+        let ptr = self.ptr;
+        write_via_c_file(w, |fp| {
+            cpp!(unsafe [ptr as "unitcell*", fp as "FILE*"] {
+                ptr->draw_domain_gnuplot(fp);
+            });
+            Ok(())
+        })
+    }
+
+    /// Writes an outline of the primary domain in the POV-Ray format.
+    pub fn draw_domain_pov(&self, w: &mut dyn Write) -> io::Result<()> {
+        let ptr = self.ptr;
+        write_via_c_file(w, |fp| {
+            cpp!(unsafe [ptr as "unitcell*", fp as "FILE*"] {
+                ptr->draw_domain_pov(fp);
+            });
+            Ok(())
+        })
+    }
+}
+
+impl Drop for UnitCell {
+    fn drop(&mut self) {
+        let ptr = self.ptr;
+        cpp!(unsafe [ptr as "unitcell*"] {
+            delete ptr;
+        })
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::VoronoiCellBase;
+    let cube = UnitCell::new(&[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 2.0]]).unwrap();
+    let cell = cube.cell();
+    assert!(cell.number_of_faces() == 6);
+    assert!((cell.volume() - 2.0).abs() < 1e-12);
+    assert!(cell.centroid().iter().all(|x| x.abs() < 1e-12));
+    let volume = cube.intersects_image(&[0, 0, 0]).unwrap();
+    assert!((volume - 2.0).abs() < 1e-12);
+    assert!(cube.intersects_image(&[3, 0, 0]).is_none());
+
+    // The Wigner-Seitz cell of the face-centered cubic lattice is a rhombic
+    // dodecahedron.
+    let fcc = UnitCell::new(&[
+        [1.0, 0.0, 0.0],
+        [0.5, 0.75_f64.sqrt(), 0.0],
+        [0.5, (1.0_f64 / 12.0).sqrt(), (2.0_f64 / 3.0).sqrt()],
+    ])
+    .unwrap();
+    let cell = fcc.cell();
+    assert!(cell.number_of_faces() == 12);
+    assert!((cell.volume() - 0.5_f64.sqrt()).abs() < 1e-12);
+    let images = fcc.images();
+    assert!(images.len() > 1);
+    assert!(images.iter().any(|(image, _)| *image == [0, 0, 0]));
+    assert!(images.iter().all(|(_, volume)| *volume > 0.0));
+    let total: f64 = images.iter().map(|(_, volume)| volume).sum();
+    assert!((total - cell.volume()).abs() < 1e-9);
+    for (image, volume) in images {
+        assert!(fcc.intersects_image(&image) == Some(volume));
+    }
+    let lattice = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]];
+    match UnitCell::new(&lattice) {
+        Err(Error::InvalidLattice(msg)) => assert!(msg.contains("positive")),
+        _ => panic!(),
+    }
+    let mut gnuplot = vec![];
+    cube.draw_domain_gnuplot(&mut gnuplot).unwrap();
+    assert!(String::from_utf8(gnuplot).unwrap().contains("1 1 2"));
+    let mut pov = vec![];
+    cube.draw_domain_pov(&mut pov).unwrap();
+    assert!(String::from_utf8(pov).unwrap().contains("cylinder{"));
+}
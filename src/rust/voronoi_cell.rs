@@ -1,5 +1,11 @@
+use crate::rust::cell_geometry::CellGeometry;
+use crate::rust::cell_stats::CellStats;
+use crate::rust::error::Error;
+use crate::rust::polyhedron;
 use crate::rust::voronoi_cell_base::{VoronoiCellBase, VoronoiCellBaseFFI};
+use crate::rust::voronoi_cell_neighbor::VoronoiCellNeighbor;
 use cpp::cpp;
+use std::convert::TryFrom;
 
 cpp! {{
     #include "voro++.hh"
@@ -19,12 +25,61 @@ cpp! {{
 /// Use this class in cases when is __not__ necessary to track the IDs of
 /// neighboring particles associated with each face of the Voronoi cell.
 #[repr(C)]
-pub struct VoronoiCell(*mut std::ffi::c_void);
+pub struct VoronoiCell(pub(crate) *mut std::ffi::c_void);
 
 impl VoronoiCellBaseFFI for VoronoiCell {
     fn ptr(&self) -> *mut std::ffi::c_void {
         self.0
     }
+
+    fn nplane_rsq(&mut self, xyz: &[f64; 3], rsq: f64, p_id: i32) -> Result<bool, Error> {
+        let ptr = self.0;
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell*", xyz as "double*", rsq as "double",
+                    p_id as "int", e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return ptr->nplane(xyz[0], xyz[1], xyz[2], rsq, p_id);
+            });
+        });
+        Error::check(retval, error)
+    }
+
+    unsafe fn cut_by_wall(
+        &mut self,
+        wall: *mut std::ffi::c_void,
+        xyz: &[f64; 3],
+    ) -> Result<bool, Error> {
+        let ptr = self.0;
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell*", wall as "wall*", xyz as "double*",
+                    e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return wall->cut_cell(*ptr, xyz[0], xyz[1], xyz[2]);
+            });
+        });
+        Error::check(retval, error)
+    }
+
+    fn cell_geometry(&self) -> CellGeometry {
+        let ptr = self.0;
+        let mut geometry = CellGeometry::default();
+        let g = &mut geometry;
+        cpp!(unsafe [ptr as "voronoicell*", g as "void*"] {
+            voroxx_cell_geometry(*ptr, g);
+        });
+        geometry
+    }
+
+    fn cell_stats(&self, codes: &[u8], stats: &mut CellStats) {
+        let ptr = self.0;
+        let data = codes.as_ptr();
+        let len = codes.len();
+        cpp!(unsafe [ptr as "voronoicell*", data as "const char*", len as "size_t", stats as "void*"] {
+            voroxx_cell_stats(*ptr, data, len, stats);
+        });
+    }
 }
 
 impl VoronoiCellBase for VoronoiCell {}
@@ -85,6 +140,65 @@ impl VoronoiCell {
         }))
     }
 
+    /// Initializes the cell to be an arbitrary convex polyhedron.
+    ///
+    /// __Parameters:__
+    /// * `vertices` The vertex coordinates.
+    /// * `faces` The vertices of each face, as indices into the vertices list.
+    ///   The vertices of every face must be ordered the same way, either
+    ///   clockwise or counterclockwise as seen from outside of the polyhedron.
+    ///
+    /// __Returns:__
+    ///     An error if the faces do not form the closed surface of a convex
+    ///     polyhedron, or if a memory limit was exceeded.
+    pub fn init_polyhedron(vertices: &[[f64; 3]], faces: &[Vec<usize>]) -> Result<Self, Error> {
+        let poly = polyhedron::build(vertices, faces, None)?;
+        let p = poly.orders.len() as i32;
+        let v = poly.vertices.as_ptr();
+        let nu = poly.orders.as_ptr();
+        let ed = poly.edges.as_ptr();
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [p as "int", v as "const double*", nu as "const int*",
+                    ed as "const int*", e as "void*"] -> *mut std::ffi::c_void as "voronoicell*" {
+            voronoicell* x = new voronoicell;
+            if(voroxx_try(e, [&] { x->init_polyhedron(p, v, nu, ed); })) return x;
+            delete x;
+            return nullptr;
+        });
+        Error::check(ptr, error).map(Self)
+    }
+
+    /// Initializes the cell to be the convex polyhedron which is the
+    /// intersection of the given half-spaces, by cutting a bounding box with
+    /// each of them. See the method
+    /// `VoronoiCellNeighbor::init_convex_polyhedron` for keeping track of
+    /// which half-space made each face.
+    ///
+    /// __Parameters:__
+    /// * `planes` A list of the pairs (`normal`, `offset`), each of which is
+    ///   the half-space of points `v` which satisfy `v · normal <= offset`, as
+    ///   for the method `VoronoiCellBase::cut_halfspace`.
+    ///
+    /// __Returns:__
+    ///     An error if the intersection of the half-spaces is empty or
+    ///     unbounded, or if a memory limit was exceeded.
+    pub fn init_convex_polyhedron(planes: &[([f64; 3], f64)]) -> Result<Self, Error> {
+        VoronoiCellNeighbor::init_convex_polyhedron(planes).map(|cell| Self::from(&cell))
+    }
+
+    /// Initializes the cell to be the convex hull of the given points.
+    ///
+    /// __Parameters:__
+    /// * `points` The points, which must not all lie on the same plane.
+    ///
+    /// __Returns:__
+    ///     An error if the convex hull has no volume, or if a memory limit was
+    ///     exceeded.
+    pub fn init_convex_hull(points: &[[f64; 3]]) -> Result<Self, Error> {
+        Self::init_convex_polyhedron(&polyhedron::hull_planes(points)?)
+    }
+
     /// Cuts a Voronoi cell using by the plane corresponding to the
     /// perpendicular bisector between the particle and the origin.
     ///
@@ -93,12 +207,40 @@ impl VoronoiCell {
     ///
     /// __Returns:__
     ///     False if the plane cut deleted the cell entirely, true otherwise.
-    pub fn plane(&mut self, xyz: &[f64; 3]) -> bool {
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    pub fn plane(&mut self, xyz: &[f64; 3]) -> Result<bool, Error> {
         let ptr = self.0;
-        return cpp!(unsafe [ptr as "voronoicell*",
-                    xyz as "double*"] -> bool as "bool" {
-            return ptr->plane(xyz[0], xyz[1], xyz[2]);
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell*",
+                    xyz as "double*", e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return ptr->plane(xyz[0], xyz[1], xyz[2]);
+            });
         });
+        Error::check(retval, error)
+    }
+
+    /// Cuts a Voronoi cell by the plane of points `v` which satisfy
+    /// `v · xyz = rsq / 2`. Choosing `rsq` other than `|xyz|²` moves the
+    /// plane away from the perpendicular bisector, which is used for
+    /// computing radical Voronoi tessellations; see function
+    /// `radical_plane_rsq`.
+    ///
+    /// __Parameters:__
+    /// * (`x`, `y`, `z`) The position of the particle.
+    /// * `rsq` The distance along the vector `xyz` at which to cut, scaled
+    ///   by twice the length of the vector.
+    ///
+    /// __Returns:__
+    ///     False if the plane cut deleted the cell entirely, true otherwise.
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    pub fn plane_weighted(&mut self, xyz: &[f64; 3], rsq: f64) -> Result<bool, Error> {
+        self.nplane_rsq(xyz, rsq, 0)
     }
 }
 
@@ -115,6 +257,34 @@ impl Clone for VoronoiCell {
     }
 }
 
+/// Copies the geometry of a Voronoi cell, discarding the neighbor information.
+impl From<&VoronoiCellNeighbor> for VoronoiCell {
+    fn from(cell: &VoronoiCellNeighbor) -> Self {
+        let ptr = cell.0;
+        Self(
+            cpp!(unsafe [ptr as "voronoicell_neighbor*"] -> *mut std::ffi::c_void as "voronoicell*" {
+                voronoicell* x = new voronoicell;
+                *x = *ptr;
+                return x;
+            }),
+        )
+    }
+}
+
+/// Reconstructs a Voronoi cell from a snapshot of its geometry. Returns an
+/// error if the snapshot does not describe a convex polyhedron.
+impl TryFrom<&CellGeometry> for VoronoiCell {
+    type Error = Error;
+    fn try_from(geometry: &CellGeometry) -> Result<Self, Error> {
+        Self::init_polyhedron(&geometry.vertices, &geometry.faces)
+    }
+}
+
+// Each cell is an independent heap object which does not refer to any other
+// data, so it can be moved between threads. It is not `Sync` because its
+// methods modify the cell's scratch space, even through a shared reference.
+unsafe impl Send for VoronoiCell {}
+
 impl Drop for VoronoiCell {
     fn drop(&mut self) {
         let ptr = self.0;
@@ -152,15 +322,37 @@ fn ffi_sanity() {
         assert!(q.len() == f.len());
     }
     assert!(x.max_radius_squared() == 3.0);
+    let edges = x.edges();
+    assert!(edges.len() == 12);
+    assert!(edges.iter().all(|&(a, b)| a < b && b < 8));
+    assert!(x.edge_lengths() == vec![1.0; 12]);
+    let adjacency = x.vertex_adjacency();
+    assert!(adjacency.len() == 8);
+    for (a, neighbors) in adjacency.iter().enumerate() {
+        assert!(neighbors.len() == 3);
+        assert!(*neighbors == x.vertex_neighbors(a));
+        for &b in neighbors {
+            assert!(edges.contains(&(a.min(b), a.max(b))));
+        }
+    }
     let mut x = x;
     x.translate(&[2.0, -2.0, 0.5]);
     x.translate(&[-2.0, 2.0, -0.5]);
     assert!(x.centroid() == [0.5, 0.5, 0.5]);
 
-    assert!(x.plane(&[10.0, 10.0, 10.0]) == true);
-    assert!(x.plane(&[1.0, 1.0, 1.0]) == true);
+    assert!(x.plane_intersects(&[1.0, 0.0, 0.0], 1.0));
+    assert!(!x.plane_intersects(&[1.0, 0.0, 0.0], 2.0));
+    assert!(x.plane_intersects(&[-1.0, -1.0, -1.0], -1.0));
+    assert!(!x.plane_intersects(&[-1.0, -1.0, -1.0], 1.0));
+    let mut y = x.clone();
+    assert!(y.cut_halfspace(&[0.0, 0.0, 3.0], 1.5, None).unwrap());
+    assert!(y.volume() == 0.5);
+    assert!(!y.cut_halfspace(&[0.0, 0.0, -1.0], -0.75, Some(1)).unwrap());
+    assert!(x.volume() == 1.0);
+    assert!(x.plane(&[10.0, 10.0, 10.0]).unwrap());
+    assert!(x.plane(&[1.0, 1.0, 1.0]).unwrap());
     x.translate(&[3.3, 3.3, 3.3]);
-    assert!(x.plane(&[1.0, 1.0, 1.0]) == false);
+    assert!(!x.plane(&[1.0, 1.0, 1.0]).unwrap());
 
     let octahedron = VoronoiCell::init_octahedron(1.0);
     assert!(octahedron.number_of_faces() == 8);
@@ -176,3 +368,142 @@ fn ffi_sanity() {
     let x = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
     assert!(x.solid_angles() == vec![2.094395102393196; 6]); //    PI*2/3
 }
+
+/// Check the Minkowski functionals of a cube against the exact values for
+/// spheres which are inside of it, and which contain it.
+#[test]
+fn minkowski() {
+    use std::f64::consts::PI;
+    let x = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let m = x.minkowski(0.5);
+    assert!((m.volume - 4.0 / 3.0 * PI * 0.125).abs() < 1e-12);
+    assert!((m.area - 4.0 * PI * 0.25).abs() < 1e-12);
+    let m = x.minkowski(2.0);
+    assert!((m.volume - 8.0).abs() < 1e-12);
+    assert!(m.area.abs() < 1e-12);
+    let radii: Vec<f64> = (0..40).map(|i| i as f64 * 0.05).collect();
+    let sweep = x.minkowski_sweep(&radii);
+    assert!(sweep.len() == radii.len());
+    for (r, m) in radii.iter().zip(&sweep) {
+        assert!(*m == x.minkowski(*r));
+    }
+    assert!(sweep.windows(2).all(|w| w[0].volume <= w[1].volume));
+}
+
+/// Build the radical Voronoi cell of a particle by cutting it with weighted
+/// planes, and check it against the exact volumes.
+#[test]
+fn weighted_planes() {
+    use crate::radical_plane_rsq;
+    let mut x = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let rsq = radical_plane_rsq(&[1.0, 0.0, 0.0], 0.5, 0.5);
+    assert!(rsq == 1.0);
+    assert!(x.plane_weighted(&[1.0, 0.0, 0.0], rsq).unwrap());
+    assert!((x.volume() - 6.0).abs() < 1e-12);
+    let rsq = radical_plane_rsq(&[-1.0, 0.0, 0.0], 0.5, 0.0);
+    assert!(x.plane_weighted(&[-1.0, 0.0, 0.0], rsq).unwrap());
+    assert!((x.volume() - 4.5).abs() < 1e-12);
+    // A particle which is much larger than this one removes the whole cell.
+    assert!(!x
+        .plane_weighted(
+            &[0.0, 1.0, 0.0],
+            radical_plane_rsq(&[0.0, 1.0, 0.0], 0.0, 3.0)
+        )
+        .unwrap());
+}
+
+/// Build cells from half-spaces and from point clouds.
+#[test]
+fn convex_polyhedra() {
+    let mut planes = vec![];
+    for i in 0..3 {
+        let mut normal = [0.0; 3];
+        normal[i] = 1.0;
+        planes.push((normal, 3.0));
+        normal[i] = -1.0;
+        planes.push((normal, -2.0));
+    }
+    let x = VoronoiCellNeighbor::init_convex_polyhedron(&planes).unwrap();
+    assert!((x.volume() - 1.0).abs() < 1e-12);
+    let mut n = x.neighbors();
+    n.sort();
+    assert!(n == vec![0, 1, 2, 3, 4, 5]);
+    assert!(x.vertices().contains(&[3.0, 3.0, 3.0]));
+    // A slanted plane which is far from the origin.
+    planes.push(([1.0, 1.0, 1.0], 8.0));
+    let x = VoronoiCell::init_convex_polyhedron(&planes).unwrap();
+    assert!((x.volume() - 5.0 / 6.0).abs() < 1e-12);
+    assert!(x.number_of_faces() == 7);
+    // Empty and unbounded intersections.
+    planes.push(([1.0, 0.0, 0.0], 1.0));
+    assert!(matches!(
+        VoronoiCell::init_convex_polyhedron(&planes),
+        Err(Error::InvalidPolyhedron(_))
+    ));
+    assert!(VoronoiCell::init_convex_polyhedron(&planes[1..6]).is_err());
+    assert!(VoronoiCell::init_convex_polyhedron(&[([0.0; 3], 1.0)]).is_err());
+
+    // The corners of a box, with some points inside of it.
+    let mut points = vec![[0.5, 0.5, 0.5], [0.2, 0.7, 0.9]];
+    for i in 0..8 {
+        points.push([(i & 1) as f64, 2.0 * (i >> 1 & 1) as f64, (i >> 2) as f64]);
+    }
+    let x = VoronoiCell::init_convex_hull(&points).unwrap();
+    assert!((x.volume() - 2.0).abs() < 1e-12);
+    assert!(x.number_of_faces() == 6);
+    assert!(x.number_of_vertices() == 8);
+    assert!(x.vertices().iter().all(|v| points[2..]
+        .iter()
+        .any(|p| (0..3).all(|i| (v[i] - p[i]).abs() < 1e-12))));
+    // Points on a sphere.
+    let points: Vec<[f64; 3]> = (0..200)
+        .map(|i| {
+            let z = 1.0 - (2 * i + 1) as f64 / 200.0;
+            let r = (1.0 - z * z).sqrt();
+            let phi = i as f64 * 2.399963229728653;
+            [10.0 + r * phi.cos(), r * phi.sin(), z]
+        })
+        .collect();
+    let x = VoronoiCell::init_convex_hull(&points).unwrap();
+    assert!(x.volume() < 4.0 / 3.0 * std::f64::consts::PI);
+    assert!(x.volume() > 4.0);
+    assert!(x.number_of_vertices() == 200);
+    // Coplanar points.
+    let flat = [[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]];
+    assert!(matches!(
+        VoronoiCell::init_convex_hull(&flat),
+        Err(Error::InvalidPolyhedron(_))
+    ));
+    assert!(VoronoiCell::init_convex_hull(&flat[..3]).is_err());
+}
+
+/// Write a cell in the POV-Ray and gnuplot formats.
+#[test]
+fn draw() {
+    let x = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let mut pov = vec![];
+    x.draw_pov(&[1.0, 0.0, 0.0], &mut pov).unwrap();
+    let pov = String::from_utf8(pov).unwrap();
+    assert!(pov.matches("sphere{").count() == 8);
+    assert!(pov.matches("cylinder{").count() == 12);
+    assert!(pov.contains("sphere{<2,1,1>,r}\n"));
+    let mut mesh = vec![];
+    x.draw_pov_mesh(&[0.0; 3], &mut mesh).unwrap();
+    let mesh = String::from_utf8(mesh).unwrap();
+    assert!(mesh.starts_with("mesh2 {\nvertex_vectors {\n8\n"));
+    assert!(mesh.contains("face_indices {\n12\n"));
+    let mut gnuplot = vec![];
+    x.draw_gnuplot(&[0.0; 3], &mut gnuplot).unwrap();
+    let gnuplot = String::from_utf8(gnuplot).unwrap();
+    assert!(gnuplot.contains("-1 -1 -1\n"));
+    for line in gnuplot.lines().filter(|l| !l.is_empty()) {
+        assert!(line
+            .split(' ')
+            .all(|c| c.parse::<f64>().unwrap().abs() == 1.0));
+    }
+    // The cell is unchanged.
+    assert!(x.number_of_edges() == 12);
+    let mut again = vec![];
+    x.draw_gnuplot(&[0.0; 3], &mut again).unwrap();
+    assert!(again == gnuplot.as_bytes());
+}
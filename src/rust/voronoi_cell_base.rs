@@ -1,28 +1,78 @@
+use crate::rust::c_file::write_via_c_file;
+use crate::rust::cell_geometry::CellGeometry;
+use crate::rust::cell_stats::CellStats;
+use crate::rust::custom_format::CustomFormat;
+use crate::rust::error::Error;
+use crate::rust::voronoi_index::VoronoiIndex;
 use cpp::cpp;
+use std::io::{self, Write};
 
 cpp! {{
     #include "voro++.hh"
     using namespace voro;
+
+    // Runs a function which cuts a Voronoi cell, and catches any errors which
+    // it throws. An error can interrupt the cut while the cell is
+    // inconsistent, so the cell is then emptied, leaving it with no
+    // vertices. Cutting an empty cell does nothing, and reports that the cell
+    // was deleted.
+    template<class F>
+    bool voroxx_try_cut(void *error, voronoicell_base *c, F f) {
+        bool retval = false;
+        if(c->p == 0) return false;
+        if(!voroxx_try(error, [&] { retval = f(); })) c->clear();
+        return retval;
+    }
 }}
 
 /// Make a rust vector with the given size and return a pointer to its internal
-/// data buffer.
+/// data buffer. The elements are initialized to their default values, since a
+/// vector must never hold uninitialized elements, even if C++ code overwrites
+/// them before they are read.
 macro_rules! vec_ptr_pair {
     ($size:expr) => {{
         let size: usize = ($size);
-        let mut rust_vec = Vec::with_capacity(size);
-        unsafe {
-            rust_vec.set_len(size);
-        }
+        let mut rust_vec = vec![Default::default(); size];
         let data_ptr = rust_vec.as_mut_ptr();
         (rust_vec, data_ptr)
     }};
 }
 
+/// The neighbor ID which cells with neighbor information record for a face
+/// whose neighbor is unknown, such as a face made by the method
+/// `VoronoiCellBase::cut_halfspace` without a plane ID. It is distinct from
+/// particle IDs, which are not negative, and from the IDs of the container
+/// boundaries and walls.
+pub const UNKNOWN_NEIGHBOR: i32 = i32::MIN;
+
 /// Private trait. Allows the public trait "VoronoiCellBase" to access the
 /// pointer to the underlying C++ structure.
 pub trait VoronoiCellBaseFFI {
     fn ptr(&self) -> *mut std::ffi::c_void;
+
+    /// Calls the method `nplane` of the underlying C++ class, which cuts the
+    /// cell by the plane `v · xyz = rsq / 2`.
+    fn nplane_rsq(&mut self, xyz: &[f64; 3], rsq: f64, p_id: i32) -> Result<bool, Error>;
+
+    /// Calls the overload of `wall::cut_cell` for the underlying C++ class.
+    ///
+    /// # Safety
+    ///
+    /// The wall must be a pointer to a live C++ wall object.
+    unsafe fn cut_by_wall(
+        &mut self,
+        wall: *mut std::ffi::c_void,
+        xyz: &[f64; 3],
+    ) -> Result<bool, Error>;
+
+    /// Calls the C++ function `voroxx_cell_geometry` for the underlying C++
+    /// class.
+    fn cell_geometry(&self) -> CellGeometry;
+
+    /// Calls the C++ function `voroxx_cell_stats` for the underlying C++
+    /// class, which computes the statistics with the given control sequence
+    /// characters.
+    fn cell_stats(&self, codes: &[u8], stats: &mut CellStats);
 }
 
 /// Methods which are common to both variants of Voronoi cells.
@@ -54,12 +104,84 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
     /// Translates the vertices of the Voronoi cell by a given vector.
     fn translate(&mut self, xyz: &[f64; 3]) {
         let ptr = self.ptr();
-        cpp!(unsafe [ptr as "voronoicell_base*",
-            xyz as "double*"] -> f64 as "double" {
+        cpp!(unsafe [ptr as "voronoicell_base*", xyz as "double*"] {
             ptr->translate(xyz[0], xyz[1], xyz[2]);
         });
     }
 
+    /// Cuts the Voronoi cell by the plane of points `v` which satisfy
+    /// `v · normal = rsq / 2`, in the local coordinates of the cell. The part
+    /// of the cell where `v · normal < rsq / 2` is kept, which contains the
+    /// origin only if `rsq` is positive. Cells with neighbor information
+    /// record the plane ID as the neighbor of the new face.
+    ///
+    /// __Parameters:__
+    /// * `normal` A normal vector to the plane.
+    /// * `rsq` The distance along the normal vector at which to cut, scaled
+    ///   by twice the length of the normal vector.
+    /// * `p_id` The plane ID number for tracking neighbors.
+    ///
+    /// __Returns:__
+    ///     False if the plane cut deleted the cell entirely, true otherwise.
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    fn cut_plane(&mut self, normal: &[f64; 3], rsq: f64, p_id: i32) -> Result<bool, Error> {
+        self.nplane_rsq(normal, rsq, p_id)
+    }
+
+    /// Cuts the Voronoi cell by the half-space of points `v` which satisfy
+    /// `v · normal <= offset`, in the local coordinates of the cell. Unlike
+    /// the method `cut_plane`, the normal vector does not need to be scaled.
+    ///
+    /// __Parameters:__
+    /// * `normal` The outward normal vector of the half-space.
+    /// * `offset` The distance along the normal vector of the boundary of the
+    ///   half-space, scaled by the length of the normal vector.
+    /// * `id` The plane ID number which cells with neighbor information record
+    ///   as the neighbor of the new face, or `UNKNOWN_NEIGHBOR` if it is None.
+    ///
+    /// __Returns:__
+    ///     False if the cut deleted the cell entirely, true otherwise.
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    fn cut_halfspace(
+        &mut self,
+        normal: &[f64; 3],
+        offset: f64,
+        id: Option<i32>,
+    ) -> Result<bool, Error> {
+        self.nplane_rsq(normal, 2.0 * offset, id.unwrap_or(UNKNOWN_NEIGHBOR))
+    }
+
+    /// Tests to see whether the plane of points `v` which satisfy
+    /// `v · normal = rsq / 2` would cut the Voronoi cell, without modifying
+    /// the cell. The plane is in the same form as for the method `cut_plane`.
+    ///
+    /// __Parameters:__
+    /// * `normal` A normal vector to the plane.
+    /// * `rsq` The distance along the normal vector of the plane, scaled by
+    ///   twice the length of the normal vector.
+    ///
+    /// __Returns:__
+    ///     True if any vertex of the cell is on the far side of the plane,
+    ///     false otherwise. Vertices which are exactly on the plane may be
+    ///     counted either way.
+    fn plane_intersects(&self, normal: &[f64; 3], rsq: f64) -> bool {
+        let ptr = self.ptr();
+        cpp!(unsafe [ptr as "voronoicell_base*", normal as "double*", rsq as "double"]
+                -> bool as "bool" {
+            return ptr->p > 0 && ptr->plane_intersects_guess(normal[0], normal[1], normal[2], rsq);
+        })
+    }
+
+    /// Copies the geometry of the Voronoi cell into plain Rust data, which
+    /// includes the neighbor information if the cell tracks it.
+    fn geometry(&self) -> CellGeometry {
+        self.cell_geometry()
+    }
+
     /// Calculates the total surface area of the Voronoi cell.
     fn surface_area(&self) -> f64 {
         let ptr = self.ptr();
@@ -97,7 +219,7 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
         cpp!(unsafe [ptr as "voronoicell_base*", x as "double*"] {
             ptr->centroid(x[0], x[1], x[2]);
         });
-        return c;
+        c
     }
 
     /// Returns a vector of the vertex coordinates using the local coordinate system.
@@ -109,7 +231,24 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
             ptr->vertices(temp);
             std::copy(temp.begin(), temp.end(), data_ptr);
         });
-        return coords;
+        coords
+    }
+
+    /// Returns a vector of the vertex coordinates in the global coordinate
+    /// system, by adding the position of the particle to each vertex.
+    ///
+    /// __Parameters:__
+    /// * `origin` The position of the particle in the global coordinate
+    ///   system.
+    fn vertices_at(&self, origin: &[f64; 3]) -> Vec<[f64; 3]> {
+        let ptr = self.ptr();
+        let (coords, data_ptr) = vec_ptr_pair!(self.number_of_vertices() as usize);
+        cpp!(unsafe [ptr as "voronoicell_base*", origin as "double*", data_ptr as "double*"] {
+            std::vector<double> temp;
+            ptr->vertices(origin[0], origin[1], origin[2], temp);
+            std::copy(temp.begin(), temp.end(), data_ptr);
+        });
+        coords
     }
 
     /// Calculates the solid angle of each face of the Voronoi cell.
@@ -121,7 +260,7 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
             ptr->solid_angles(temp);
             std::copy(temp.begin(), temp.end(), data_ptr);
         });
-        return areas;
+        areas
     }
 
     /// Calculates the areas of each face of the Voronoi cell.
@@ -133,7 +272,7 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
             ptr->face_areas(temp);
             std::copy(temp.begin(), temp.end(), data_ptr);
         });
-        return areas;
+        areas
     }
 
     /// Calculates the perimeters of each face.
@@ -145,7 +284,7 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
             ptr->face_perimeters(temp);
             std::copy(temp.begin(), temp.end(), data_ptr);
         });
-        return perimeters;
+        perimeters
     }
 
     /// Returns the vertices that make up each face of the Voronoi cell, as
@@ -178,11 +317,137 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
                 }
             });
         });
-        return retval;
+        retval
     }
 
-    /// Calculates the normal vector of each face of the Voronoi cell, and
-    /// scales it to the distance from the cell center to that plane.
+    /// Returns the edges of the Voronoi cell, as pairs of indices into the
+    /// vertices list. Each edge is listed once, with the smaller index first.
+    fn edges(&self) -> Vec<(usize, usize)> {
+        let ptr = self.ptr();
+        let mut retval = vec![];
+        let ptr_retval = &mut retval;
+        cpp!(unsafe [ptr as "voronoicell_base*", ptr_retval as "void*"] {
+            std::vector<int> temp;
+            for(int i = 0; i < ptr->p; i++) {
+                for(int j = 0; j < ptr->nu[i]; j++) {
+                    int k = ptr->ed[i][j];
+                    if(i < k) {
+                        temp.push_back(i);
+                        temp.push_back(k);
+                    }
+                }
+            }
+            int *data = temp.data();
+            int len = temp.size();
+            rust!(_unused_name [data: *const i32 as "int*", len: i32 as "int",
+                                ptr_retval: &mut Vec<(usize, usize)> as "void*"] {
+                if len > 0 {
+                    let data = std::slice::from_raw_parts(data, len as usize);
+                    ptr_retval.extend(data.chunks(2).map(|e| (e[0] as usize, e[1] as usize)));
+                }
+            });
+        });
+        retval
+    }
+
+    /// Calculates the length of each edge of the Voronoi cell, in the same
+    /// order as the method `edges`.
+    fn edge_lengths(&self) -> Vec<f64> {
+        let vertices = self.vertices();
+        self.edges()
+            .into_iter()
+            .map(|(a, b)| {
+                let (a, b) = (vertices[a], vertices[b]);
+                ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+            })
+            .collect()
+    }
+
+    /// Returns the vertices which are connected to the given vertex by an
+    /// edge, as indices into the vertices list. They are listed in the cyclic
+    /// order in which the edges are stored around the vertex.
+    ///
+    /// __Panics:__
+    ///     If the vertex index is out of bounds.
+    fn vertex_neighbors(&self, vertex: usize) -> Vec<usize> {
+        assert!(vertex < self.number_of_vertices() as usize);
+        let ptr = self.ptr();
+        let vertex = vertex as i32;
+        let order = cpp!(unsafe [ptr as "voronoicell_base*", vertex as "int"] -> i32 as "int" {
+            return ptr->nu[vertex];
+        });
+        let (neighbors, data_ptr) = vec_ptr_pair!(order as usize);
+        cpp!(unsafe [ptr as "voronoicell_base*", vertex as "int", data_ptr as "size_t*"] {
+            for(int j = 0; j < ptr->nu[vertex]; j++) data_ptr[j] = ptr->ed[vertex][j];
+        });
+        neighbors
+    }
+
+    /// Returns the vertices which are connected to each vertex by an edge.
+    /// This is the same as calling the method `vertex_neighbors` for every
+    /// vertex.
+    fn vertex_adjacency(&self) -> Vec<Vec<usize>> {
+        (0..self.number_of_vertices() as usize)
+            .map(|v| self.vertex_neighbors(v))
+            .collect()
+    }
+
+    /// Returns the order of each vertex, which is the number of edges that
+    /// meet at the vertex, in the same order as the vertices list.
+    fn vertex_orders(&self) -> Vec<i32> {
+        let ptr = self.ptr();
+        let (orders, data_ptr) = vec_ptr_pair!(self.number_of_vertices() as usize);
+        cpp!(unsafe [ptr as "voronoicell_base*", data_ptr as "int*"] {
+            std::vector<int> temp;
+            ptr->vertex_orders(temp);
+            std::copy(temp.begin(), temp.end(), data_ptr);
+        });
+        orders
+    }
+
+    /// Returns the order of each face, which is the number of edges that the
+    /// face has, in the same order as the method `face_vertices`.
+    fn face_orders(&self) -> Vec<i32> {
+        let ptr = self.ptr();
+        let (orders, data_ptr) = vec_ptr_pair!(self.number_of_faces() as usize);
+        cpp!(unsafe [ptr as "voronoicell_base*", data_ptr as "int*"] {
+            std::vector<int> temp;
+            ptr->face_orders(temp);
+            std::copy(temp.begin(), temp.end(), data_ptr);
+        });
+        orders
+    }
+
+    /// Computes the frequency table of the face orders. The entry at index
+    /// `n` is the number of faces which have `n` edges. The table ends at the
+    /// largest face order.
+    fn face_freq_table(&self) -> Vec<i32> {
+        let ptr = self.ptr();
+        let mut retval = vec![];
+        let ptr_retval = &mut retval;
+        cpp!(unsafe [ptr as "voronoicell_base*", ptr_retval as "void*"] {
+            std::vector<int> temp;
+            ptr->face_freq_table(temp);
+            int *data = temp.data();
+            int len = temp.size();
+            rust!(_unused_name [data: *const i32 as "int*", len: i32 as "int",
+                                ptr_retval: &mut Vec<i32> as "void*"] {
+                if len > 0 {
+                    ptr_retval.extend_from_slice(std::slice::from_raw_parts(data, len as usize));
+                }
+            });
+        });
+        retval
+    }
+
+    /// Computes the Voronoi index of the cell, which counts its faces by their
+    /// number of edges.
+    fn voronoi_index(&self) -> VoronoiIndex {
+        VoronoiIndex::from_face_freq_table(&self.face_freq_table())
+    }
+
+    /// Calculates the outward unit normal vector of each face of the Voronoi
+    /// cell, in the same order as the method `face_vertices`.
     fn normals(&self) -> Vec<[f64; 3]> {
         let ptr = self.ptr();
         let (normals, data_ptr) = vec_ptr_pair!(self.number_of_faces() as usize);
@@ -191,7 +456,47 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
             ptr->normals(temp);
             std::copy(temp.begin(), temp.end(), data_ptr);
         });
-        return normals;
+        normals
+    }
+
+    /// Computes the Minkowski functionals of the intersection of the Voronoi
+    /// cell with a sphere, which is centered on the origin of the local
+    /// coordinate system.
+    ///
+    /// __Parameters:__
+    /// * `r` The radius of the sphere.
+    fn minkowski(&self, r: f64) -> Minkowski {
+        let ptr = self.ptr();
+        let mut m = Minkowski::default();
+        let area = &mut m.area;
+        let volume = &mut m.volume;
+        cpp!(unsafe [ptr as "voronoicell_base*", r as "double",
+                area as "double*", volume as "double*"] {
+            ptr->minkowski(r, *area, *volume);
+        });
+        m
+    }
+
+    /// Computes the Minkowski functionals of the Voronoi cell for many radii
+    /// at once. This is the same as calling the method `minkowski` for each
+    /// of the radii.
+    ///
+    /// __Parameters:__
+    /// * `radii` The radii of the spheres.
+    ///
+    /// Returns the Minkowski functionals in the same order as the radii.
+    fn minkowski_sweep(&self, radii: &[f64]) -> Vec<Minkowski> {
+        let ptr = self.ptr();
+        let (retval, data_ptr) = vec_ptr_pair!(radii.len());
+        let radii_ptr = radii.as_ptr();
+        let len = radii.len();
+        cpp!(unsafe [ptr as "voronoicell_base*", radii_ptr as "const double*",
+                len as "size_t", data_ptr as "double*"] {
+            for(size_t i = 0; i < len; i++) {
+                ptr->minkowski(radii_ptr[i], data_ptr[2 * i], data_ptr[2 * i + 1]);
+            }
+        });
+        retval
     }
 
     /// Calculates the maximum radius squared of any vertex from the center of
@@ -203,4 +508,118 @@ pub trait VoronoiCellBase: VoronoiCellBaseFFI {
             return ptr->max_radius_squared() * 0.25;
         })
     }
+
+    /// Writes the Voronoi cell in the POV-Ray format, as a sphere at each
+    /// vertex and a cylinder along each edge. The radius of the spheres and
+    /// cylinders is the POV-Ray variable `r`.
+    ///
+    /// __Parameters:__
+    /// * `xyz` A displacement vector to be added to the cell's position.
+    /// * `w` The output.
+    fn draw_pov(&self, xyz: &[f64; 3], w: &mut dyn Write) -> io::Result<()> {
+        let ptr = self.ptr();
+        write_via_c_file(w, |fp| {
+            cpp!(unsafe [ptr as "voronoicell_base*", xyz as "double*", fp as "FILE*"] {
+                ptr->draw_pov(xyz[0], xyz[1], xyz[2], fp);
+            });
+            Ok(())
+        })
+    }
+
+    /// Writes the Voronoi cell in the POV-Ray format, as a `mesh2` object.
+    ///
+    /// __Parameters:__
+    /// * `xyz` A displacement vector to be added to the cell's position.
+    /// * `w` The output.
+    fn draw_pov_mesh(&self, xyz: &[f64; 3], w: &mut dyn Write) -> io::Result<()> {
+        let ptr = self.ptr();
+        write_via_c_file(w, |fp| {
+            cpp!(unsafe [ptr as "voronoicell_base*", xyz as "double*", fp as "FILE*"] {
+                ptr->draw_pov_mesh(xyz[0], xyz[1], xyz[2], fp);
+            });
+            Ok(())
+        })
+    }
+
+    /// Writes the edges of the Voronoi cell in the gnuplot format.
+    ///
+    /// __Parameters:__
+    /// * `xyz` A displacement vector to be added to the cell's position.
+    /// * `w` The output.
+    fn draw_gnuplot(&self, xyz: &[f64; 3], w: &mut dyn Write) -> io::Result<()> {
+        let ptr = self.ptr();
+        write_via_c_file(w, |fp| {
+            cpp!(unsafe [ptr as "voronoicell_base*", xyz as "double*", fp as "FILE*"] {
+                ptr->draw_gnuplot(xyz[0], xyz[1], xyz[2], fp);
+            });
+            Ok(())
+        })
+    }
+
+    /// Formats information about the Voronoi cell, using the custom output
+    /// format of Voro++.
+    ///
+    /// __Parameters:__
+    /// * `format` The custom format string.
+    /// * `id` The ID of the particle.
+    /// * `xyz` The position of the particle.
+    /// * `radius` The radius of the particle.
+    ///
+    /// __Returns:__
+    ///     The formatted text, without a trailing newline. Returns an error if
+    ///     the temporary file for the output could not be made.
+    fn output_custom(
+        &self,
+        format: &CustomFormat,
+        id: i32,
+        xyz: &[f64; 3],
+        radius: f64,
+    ) -> io::Result<String> {
+        let ptr = self.ptr();
+        let format = format.as_c_str().as_ptr();
+        let mut output = vec![];
+        write_via_c_file(&mut output, |fp| {
+            cpp!(unsafe [ptr as "voronoicell_base*", format as "const char*", id as "int",
+                    xyz as "double*", radius as "double", fp as "FILE*"] {
+                ptr->output_custom(format, id, xyz[0], xyz[1], xyz[2], radius, fp);
+            });
+            Ok(())
+        })?;
+        if output.last() == Some(&b'\n') {
+            output.pop();
+        }
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
+
+/// Computes the position of the plane between two particles in a radical
+/// Voronoi tessellation, also known as a power diagram or a Laguerre
+/// tessellation. The plane is where the power distances from the particles
+/// are equal, and it is in the form which is used by the methods
+/// `VoronoiCell::plane_weighted` and `VoronoiCellNeighbor::nplane_weighted`.
+///
+/// __Parameters:__
+/// * `xyz` The position of the other particle, relative to the particle whose
+///   cell is being computed.
+/// * `radius` The radius of the particle whose cell is being computed.
+/// * `other_radius` The radius of the other particle.
+///
+/// Returns the value `rsq` such that the radical plane is the set of points
+/// `v` which satisfy `v · xyz = rsq / 2`.
+pub fn radical_plane_rsq(xyz: &[f64; 3], radius: f64, other_radius: f64) -> f64 {
+    xyz[0] * xyz[0] + xyz[1] * xyz[1] + xyz[2] * xyz[2] + radius * radius
+        - other_radius * other_radius
+}
+
+/// The Minkowski functionals of the intersection of a Voronoi cell with a
+/// sphere. See method `VoronoiCellBase::minkowski`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Minkowski {
+    /// The area of the part of the sphere's surface which is inside of the
+    /// Voronoi cell.
+    pub area: f64,
+
+    /// The volume of the intersection of the sphere and the Voronoi cell.
+    pub volume: f64,
 }
@@ -1,5 +1,11 @@
-use crate::rust::voronoi_cell_base::{VoronoiCellBase, VoronoiCellBaseFFI};
+use crate::rust::cell_geometry::CellGeometry;
+use crate::rust::cell_stats::CellStats;
+use crate::rust::error::Error;
+use crate::rust::polyhedron;
+use crate::rust::voronoi_cell::VoronoiCell;
+use crate::rust::voronoi_cell_base::{VoronoiCellBase, VoronoiCellBaseFFI, UNKNOWN_NEIGHBOR};
 use cpp::cpp;
+use std::convert::TryFrom;
 
 cpp! {{
     #include "voro++.hh"
@@ -19,12 +25,61 @@ cpp! {{
 /// Use this class in cases when is necessary to track the IDs of
 /// neighboring particles associated with each face of the Voronoi cell.
 #[repr(C)]
-pub struct VoronoiCellNeighbor(*mut std::ffi::c_void);
+pub struct VoronoiCellNeighbor(pub(crate) *mut std::ffi::c_void);
 
 impl VoronoiCellBaseFFI for VoronoiCellNeighbor {
     fn ptr(&self) -> *mut std::ffi::c_void {
         self.0
     }
+
+    fn nplane_rsq(&mut self, xyz: &[f64; 3], rsq: f64, p_id: i32) -> Result<bool, Error> {
+        let ptr = self.0;
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell_neighbor*", xyz as "double*", rsq as "double",
+                    p_id as "int", e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return ptr->nplane(xyz[0], xyz[1], xyz[2], rsq, p_id);
+            });
+        });
+        Error::check(retval, error)
+    }
+
+    unsafe fn cut_by_wall(
+        &mut self,
+        wall: *mut std::ffi::c_void,
+        xyz: &[f64; 3],
+    ) -> Result<bool, Error> {
+        let ptr = self.0;
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell_neighbor*", wall as "wall*", xyz as "double*",
+                    e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return wall->cut_cell(*ptr, xyz[0], xyz[1], xyz[2]);
+            });
+        });
+        Error::check(retval, error)
+    }
+
+    fn cell_geometry(&self) -> CellGeometry {
+        let ptr = self.0;
+        let mut geometry = CellGeometry::default();
+        let g = &mut geometry;
+        cpp!(unsafe [ptr as "voronoicell_neighbor*", g as "void*"] {
+            voroxx_cell_geometry(*ptr, g);
+        });
+        geometry
+    }
+
+    fn cell_stats(&self, codes: &[u8], stats: &mut CellStats) {
+        let ptr = self.0;
+        let data = codes.as_ptr();
+        let len = codes.len();
+        cpp!(unsafe [ptr as "voronoicell_neighbor*", data as "const char*", len as "size_t", stats as "void*"] {
+            voroxx_cell_stats(*ptr, data, len, stats);
+        });
+    }
 }
 
 impl VoronoiCellBase for VoronoiCellNeighbor {}
@@ -35,7 +90,6 @@ impl VoronoiCellNeighbor {
     /// ID numbers from -1 to -6.
     ///
     /// __Parameters:__
-
     /// * `xyz_min` The minimum coordinates.
     /// * `xyz_max` The maximum coordinates.
     pub fn init(xyz_min: &[f64; 3], xyz_max: &[f64; 3]) -> Self {
@@ -89,6 +143,93 @@ impl VoronoiCellNeighbor {
         }))
     }
 
+    /// Initializes the cell to be an arbitrary convex polyhedron.
+    ///
+    /// __Parameters:__
+    /// * `vertices` The vertex coordinates.
+    /// * `faces` The vertices of each face, as indices into the vertices list.
+    ///   The vertices of every face must be ordered the same way, either
+    ///   clockwise or counterclockwise as seen from outside of the polyhedron.
+    /// * `neighbors` The neighbor information for each face, in the same
+    ///   order as the faces.
+    ///
+    /// __Returns:__
+    ///     An error if the faces do not form the closed surface of a convex
+    ///     polyhedron, or if a memory limit was exceeded.
+    pub fn init_polyhedron(
+        vertices: &[[f64; 3]],
+        faces: &[Vec<usize>],
+        neighbors: &[i32],
+    ) -> Result<Self, Error> {
+        let poly = polyhedron::build(vertices, faces, Some(neighbors))?;
+        let p = poly.orders.len() as i32;
+        let v = poly.vertices.as_ptr();
+        let nu = poly.orders.as_ptr();
+        let ed = poly.edges.as_ptr();
+        let ne = poly.neighbors.as_ptr();
+        let mut error = None;
+        let e = &mut error;
+        let ptr = cpp!(unsafe [p as "int", v as "const double*", nu as "const int*",
+                    ed as "const int*", ne as "const int*", e as "void*"]
+                    -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            voronoicell_neighbor* x = new voronoicell_neighbor;
+            if(voroxx_try(e, [&] { x->init_polyhedron(p, v, nu, ed, ne); })) return x;
+            delete x;
+            return nullptr;
+        });
+        Error::check(ptr, error).map(Self)
+    }
+
+    /// Initializes the cell to be the convex polyhedron which is the
+    /// intersection of the given half-spaces, by cutting a bounding box with
+    /// each of them. The neighbor information for each face is the index of
+    /// its half-space in the list `planes`.
+    ///
+    /// __Parameters:__
+    /// * `planes` A list of the pairs (`normal`, `offset`), each of which is
+    ///   the half-space of points `v` which satisfy `v · normal <= offset`, as
+    ///   for the method `VoronoiCellBase::cut_halfspace`.
+    ///
+    /// __Returns:__
+    ///     An error if the intersection of the half-spaces is empty or
+    ///     unbounded, or if a memory limit was exceeded.
+    pub fn init_convex_polyhedron(planes: &[([f64; 3], f64)]) -> Result<Self, Error> {
+        let mut radius = 0.0f64;
+        for (normal, offset) in planes {
+            let len = normal.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if !(len > 0.0 && len.is_finite() && offset.is_finite()) {
+                return Err(Error::InvalidPolyhedron(
+                    "Half-space is not valid".to_string(),
+                ));
+            }
+            radius = radius.max(offset.abs() / len);
+        }
+        // Every boundary plane is strictly inside of the bounding box, so the
+        // polyhedron is bounded if none of the faces of the box remain. The
+        // box grows if the polyhedron is far away from the origin.
+        let mut l = if radius > 0.0 { 2.0 * radius } else { 1.0 };
+        let mut empty = false;
+        for _ in 0..16 {
+            let mut cell = Self::init(&[-l; 3], &[l; 3]);
+            empty = false;
+            for (i, (normal, offset)) in planes.iter().enumerate() {
+                if !cell.cut_halfspace(normal, *offset, Some(i as i32))? {
+                    empty = true;
+                    break;
+                }
+            }
+            if !empty && cell.neighbors().iter().all(|&n| n >= 0) {
+                return Ok(cell);
+            }
+            l *= 4.0;
+        }
+        Err(Error::InvalidPolyhedron(if empty {
+            "Intersection of half-spaces is empty".to_string()
+        } else {
+            "Intersection of half-spaces is unbounded".to_string()
+        }))
+    }
+
     /// Cuts a Voronoi cell using the plane corresponding to the perpendicular
     /// bisector between the particle and the origin. This sets the plane ID
     /// number to zero; see method `nplane` to supply a different plane ID
@@ -99,12 +240,20 @@ impl VoronoiCellNeighbor {
     ///
     /// __Returns:__
     ///     False if the plane cut deleted the cell entirely, true otherwise.
-    pub fn plane(&mut self, xyz: &[f64; 3]) -> bool {
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    pub fn plane(&mut self, xyz: &[f64; 3]) -> Result<bool, Error> {
         let ptr = self.0;
-        cpp!(unsafe [ptr as "voronoicell_neighbor*",
-                    xyz as "double*"] -> bool as "bool" {
-            return ptr->plane(xyz[0], xyz[1], xyz[2]);
-        })
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell_neighbor*",
+                    xyz as "double*", e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return ptr->plane(xyz[0], xyz[1], xyz[2]);
+            });
+        });
+        Error::check(retval, error)
     }
 
     /// Cuts a Voronoi cell using the plane corresponding to the perpendicular
@@ -116,30 +265,75 @@ impl VoronoiCellNeighbor {
     ///
     /// __Returns:__
     ///     False if the plane cut deleted the cell entirely, true otherwise.
-    pub fn nplane(&mut self, xyz: &[f64; 3], p_id: i32) -> bool {
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    pub fn nplane(&mut self, xyz: &[f64; 3], p_id: i32) -> Result<bool, Error> {
         let ptr = self.0;
-        cpp!(unsafe [ptr as "voronoicell_neighbor*",
+        let mut error = None;
+        let e = &mut error;
+        let retval = cpp!(unsafe [ptr as "voronoicell_neighbor*",
                     xyz as "double*",
-                    p_id as "int"] -> bool as "bool" {
-            return ptr->nplane(xyz[0], xyz[1], xyz[2], p_id);
-        })
+                    p_id as "int", e as "void*"] -> bool as "bool" {
+            return voroxx_try_cut(e, ptr, [&] {
+                return ptr->nplane(xyz[0], xyz[1], xyz[2], p_id);
+            });
+        });
+        Error::check(retval, error)
+    }
+
+    /// Cuts a Voronoi cell by the plane of points `v` which satisfy
+    /// `v · xyz = rsq / 2`. Choosing `rsq` other than `|xyz|²` moves the
+    /// plane away from the perpendicular bisector, which is used for
+    /// computing radical Voronoi tessellations; see function
+    /// `radical_plane_rsq`. This sets the plane ID number to zero; see method
+    /// `nplane_weighted` to supply a different plane ID number.
+    ///
+    /// __Parameters:__
+    /// * (`x`, `y`, `z`) The position of the particle.
+    /// * `rsq` The distance along the vector `xyz` at which to cut, scaled
+    ///   by twice the length of the vector.
+    ///
+    /// __Returns:__
+    ///     False if the plane cut deleted the cell entirely, true otherwise.
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    pub fn plane_weighted(&mut self, xyz: &[f64; 3], rsq: f64) -> Result<bool, Error> {
+        self.nplane_rsq(xyz, rsq, 0)
+    }
+
+    /// Same as method `plane_weighted`, but records the given plane ID number
+    /// as the neighbor of the new face.
+    ///
+    /// __Parameters:__
+    /// * (`x`, `y`, `z`) The position of the particle.
+    /// * `rsq` The distance along the vector `xyz` at which to cut, scaled
+    ///   by twice the length of the vector.
+    /// * `p_id` The plane ID number for tracking neighbors.
+    ///
+    /// __Returns:__
+    ///     False if the plane cut deleted the cell entirely, true otherwise.
+    ///     Returns an error if a memory limit was exceeded or if the cell
+    ///     became inconsistent, after which the cell is left empty, with no
+    ///     vertices.
+    pub fn nplane_weighted(&mut self, xyz: &[f64; 3], rsq: f64, p_id: i32) -> Result<bool, Error> {
+        self.nplane_rsq(xyz, rsq, p_id)
     }
 
     /// Returns a list of IDs of neighboring particles corresponding to each face.
     pub fn neighbors(&self) -> Vec<i32> {
         let ptr = self.0;
         let f = self.number_of_faces() as usize;
-        let mut neighbors = Vec::with_capacity(f);
-        unsafe {
-            neighbors.set_len(f);
-        }
+        // Initialized, like the vectors made by the macro "vec_ptr_pair".
+        let mut neighbors = vec![0; f];
         let data_ptr = neighbors.as_mut_ptr();
         cpp!(unsafe [ptr as "voronoicell_neighbor*", data_ptr as "int*"] {
             std::vector<int> temp;
             ptr->neighbors(temp);
             std::copy(temp.begin(), temp.end(), data_ptr);
         });
-        return neighbors;
+        neighbors
     }
 }
 
@@ -155,6 +349,48 @@ impl Clone for VoronoiCellNeighbor {
     }
 }
 
+/// Copies the geometry of a Voronoi cell, and adds neighbor information. The
+/// neighbor ID of every face of the new cell is `UNKNOWN_NEIGHBOR`.
+impl From<&VoronoiCell> for VoronoiCellNeighbor {
+    fn from(cell: &VoronoiCell) -> Self {
+        let ptr = cell.0;
+        let unknown = UNKNOWN_NEIGHBOR;
+        Self(cpp!(unsafe [ptr as "voronoicell*", unknown as "int"]
+                -> *mut std::ffi::c_void as "voronoicell_neighbor*" {
+            voronoicell_neighbor* x = new voronoicell_neighbor;
+            *x = *ptr;
+            for(int i = 0; i < x->p; i++) {
+                for(int j = 0; j < x->nu[i]; j++) x->ne[i][j] = unknown;
+            }
+            return x;
+        }))
+    }
+}
+
+/// Reconstructs a Voronoi cell from a snapshot of its geometry. If the
+/// snapshot has no neighbor information, then the neighbor information for
+/// every face is `UNKNOWN_NEIGHBOR`. Returns an error if the snapshot does not
+/// describe a convex polyhedron.
+impl TryFrom<&CellGeometry> for VoronoiCellNeighbor {
+    type Error = Error;
+    fn try_from(geometry: &CellGeometry) -> Result<Self, Error> {
+        let unknown;
+        let neighbors = match &geometry.neighbors {
+            Some(neighbors) => neighbors,
+            None => {
+                unknown = vec![UNKNOWN_NEIGHBOR; geometry.faces.len()];
+                &unknown
+            }
+        };
+        Self::init_polyhedron(&geometry.vertices, &geometry.faces, neighbors)
+    }
+}
+
+// Each cell is an independent heap object which does not refer to any other
+// data, so it can be moved between threads. It is not `Sync` because its
+// methods modify the cell's scratch space, even through a shared reference.
+unsafe impl Send for VoronoiCellNeighbor {}
+
 impl Drop for VoronoiCellNeighbor {
     fn drop(&mut self) {
         let ptr = self.0;
@@ -196,15 +432,27 @@ fn ffi_sanity() {
     x.translate(&[-2.0, 2.0, -0.5]);
     assert!(x.centroid() == [0.5, 0.5, 0.5]);
 
-    assert!(x.plane(&[10.0, 10.0, 10.0]) == true);
-    assert!(x.nplane(&[1.0, 1.0, 1.0], 123456) == true);
+    assert!(x.plane(&[10.0, 10.0, 10.0]).unwrap());
+    assert!(x.nplane(&[1.0, 1.0, 1.0], 123456).unwrap());
     assert!(x.neighbors().len() == x.number_of_faces() as usize);
     let mut n = x.neighbors();
     n.sort();
     assert!(n == vec![-6, -5, -4, -3, -2, -1, 123456]);
 
+    assert!(x.plane_weighted(&[1.0, 0.0, 0.0], 1.5).unwrap());
+    assert!(x.nplane_weighted(&[0.0, 1.0, 0.0], 1.5, 7).unwrap());
+    assert!(x.neighbors().contains(&0));
+    assert!(x.neighbors().contains(&7));
+    assert!(x.cut_halfspace(&[0.0, 0.0, -2.0], -0.5, Some(-99)).unwrap());
+    assert!(x.neighbors().contains(&-99));
+    assert!(!x.neighbors().contains(&-5));
+    let mut y = x.clone();
+    assert!(y.cut_halfspace(&[1.0, 1.0, 1.0], 1.0, None).unwrap());
+    assert!(y.neighbors().contains(&UNKNOWN_NEIGHBOR));
+    assert!(!x.neighbors().contains(&UNKNOWN_NEIGHBOR));
+
     x.translate(&[3.3, 3.3, 3.3]);
-    assert!(x.plane(&[1.0, 1.0, 1.0]) == false);
+    assert!(!x.plane(&[1.0, 1.0, 1.0]).unwrap());
 
     let octahedron = VoronoiCellNeighbor::init_octahedron(1.0);
     assert!(octahedron.number_of_faces() == 8);
@@ -218,3 +466,154 @@ fn ffi_sanity() {
     assert!(tetrhedron.number_of_faces() == 4);
     assert!(tetrhedron.max_radius_squared() == 3.0);
 }
+
+/// Convert cells between the variants with and without neighbor information.
+#[test]
+fn conversions() {
+    let mut x = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    assert!(x.plane(&[1.0, 1.0, 1.0]).unwrap());
+    let mut y = VoronoiCellNeighbor::from(&x);
+    assert!(y.volume() == x.volume());
+    assert!(y.vertices() == x.vertices());
+    assert!(y.face_vertices() == x.face_vertices());
+    assert!(y.neighbors() == vec![UNKNOWN_NEIGHBOR; 7]);
+    assert!(y.nplane(&[-1.0, 0.0, 0.0], 5).unwrap());
+    let mut n = y.neighbors();
+    n.sort();
+    assert!(n.iter().filter(|&&n| n == UNKNOWN_NEIGHBOR).count() == 6);
+    assert!(n.last() == Some(&5));
+    let z = VoronoiCell::from(&y);
+    assert!(z.volume() == y.volume());
+    assert!(z.vertices() == y.vertices());
+    assert!(z.face_vertices() == y.face_vertices());
+    // The cells do not share memory.
+    let volume = x.volume();
+    assert!(x.plane(&[0.0, 1.0, 0.0]).unwrap());
+    assert!(x.volume() < volume);
+    assert!(VoronoiCellNeighbor::from(&x).volume() == x.volume());
+    assert!(y.volume() == z.volume());
+}
+
+/// Restore cells from snapshots, and check that they can be cut further.
+#[test]
+fn reconstruct() {
+    let mut x = VoronoiCellNeighbor::init_octahedron(2.0);
+    assert!(x.nplane(&[1.0, 0.5, 0.0], 7).unwrap());
+    assert!(x.nplane(&[-0.5, 0.0, 1.0], 8).unwrap());
+    let g = x.geometry();
+    let mut y = VoronoiCellNeighbor::try_from(&g).unwrap();
+    let h = y.geometry();
+    assert!((h.volume - g.volume).abs() < 1e-12);
+    assert!(h.faces.len() == g.faces.len());
+    assert!(h.vertices == g.vertices);
+    let mut a = x.neighbors();
+    let mut b = y.neighbors();
+    a.sort();
+    b.sort();
+    assert!(a == b);
+    // Each face keeps its neighbor information.
+    for (face, n) in h.faces.iter().zip(h.neighbors.as_ref().unwrap()) {
+        let mut face = face.clone();
+        let i = (0..face.len()).min_by_key(|&i| face[i]).unwrap();
+        face.rotate_left(i);
+        let j = g.faces.iter().position(|f| {
+            let mut f = f.clone();
+            let i = (0..f.len()).min_by_key(|&i| f[i]).unwrap();
+            f.rotate_left(i);
+            f == face
+        });
+        assert!(g.neighbors.as_ref().unwrap()[j.unwrap()] == *n);
+    }
+    assert!(x.nplane(&[0.0, -0.6, -0.6], 9).unwrap());
+    assert!(y.nplane(&[0.0, -0.6, -0.6], 9).unwrap());
+    assert!((x.volume() - y.volume()).abs() < 1e-12);
+    assert!(x.number_of_faces() == y.number_of_faces());
+    assert!(y.neighbors().contains(&9));
+
+    // Faces may be given counterclockwise, and without neighbor information.
+    let mut g = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]).geometry();
+    g.faces.iter_mut().for_each(|f| f.reverse());
+    let z = VoronoiCellNeighbor::try_from(&g).unwrap();
+    assert!(z.volume() == 8.0);
+    assert!(z.neighbors() == vec![UNKNOWN_NEIGHBOR; 6]);
+    let mut z = VoronoiCell::try_from(&g).unwrap();
+    assert!(z.volume() == 8.0);
+    assert!(z.plane(&[1.0, 0.0, 0.0]).unwrap());
+    assert!(z.volume() == 6.0);
+}
+
+/// Format cells with the custom output format of Voro++.
+#[test]
+fn output_custom() {
+    use crate::CustomFormat;
+    let mut x = VoronoiCellNeighbor::init(&[-1.0; 3], &[1.0; 3]);
+    let format = CustomFormat::new("%i %q %r %v %s %n %a").unwrap();
+    let text = x.output_custom(&format, 3, &[1.0, 2.0, 3.5], 0.5).unwrap();
+    let neighbors: Vec<String> = x.neighbors().iter().map(i32::to_string).collect();
+    assert!(text == format!("3 1 2 3.5 0.5 8 6 {} 4 4 4 4 4 4", neighbors.join(" ")));
+    assert!(x.nplane(&[2.0, 0.0, 0.0], 9).unwrap());
+    let format = CustomFormat::new("volume=%v, faces: %s").unwrap();
+    let text = x.output_custom(&format, 0, &[0.0; 3], 0.0).unwrap();
+    assert!(text == "volume=8, faces: 6");
+    // Cells without neighbor information print nothing for "%n".
+    let y = VoronoiCell::from(&x);
+    let format = CustomFormat::new("[%n]").unwrap();
+    assert!(y.output_custom(&format, 0, &[0.0; 3], 0.0).unwrap() == "[]");
+}
+
+/// Check that a cell which failed in the middle of a plane cut is left empty,
+/// and can still be used safely.
+#[test]
+fn failed_cut() {
+    use crate::{CellStatsBuilder, CustomFormat, Minkowski};
+    // Cut the cell by many planes through the same point, until that vertex
+    // exceeds the maximum vertex order.
+    let mut x = VoronoiCellNeighbor::init(&[-1.0; 3], &[1.0; 3]);
+    let n = 3000;
+    let error = (0..n)
+        .map(|k| {
+            let t = k as f64 * 2.0 * std::f64::consts::PI / n as f64;
+            x.cut_plane(&[t.cos(), t.sin(), 1.0], 2.0, k)
+        })
+        .find_map(Result::err);
+    assert!(matches!(error, Some(Error::MemoryLimit(_))));
+
+    assert!(x.number_of_vertices() == 0);
+    assert!(x.number_of_faces() == 0);
+    assert!(x.number_of_edges() == 0);
+    assert!(x.volume() == 0.0);
+    assert!(x.surface_area() == 0.0);
+    assert!(x.total_edge_distance() == 0.0);
+    assert!(x.max_radius_squared() == 0.0);
+    assert!(x.vertices().is_empty());
+    assert!(x.face_vertices().is_empty());
+    assert!(x.face_areas().is_empty());
+    assert!(x.neighbors().is_empty());
+    assert!(x.edges().is_empty());
+    assert!(x.vertex_adjacency().is_empty());
+    assert!(x.minkowski(1.0) == Minkowski::default());
+    assert!(!x.plane_intersects(&[1.0, 0.0, 0.0], 0.0));
+    let _ = (x.centroid(), x.solid_angles(), x.vertices_at(&[1.0; 3]));
+    let _ = (x.face_perimeters(), x.edge_lengths(), x.vertex_orders());
+    let _ = (
+        x.face_orders(),
+        x.face_freq_table(),
+        x.voronoi_index(),
+        x.normals(),
+    );
+    let _ = (x.minkowski_sweep(&[0.5, 1.0]), x.geometry(), x.clone());
+    let _ = CellStatsBuilder::all().compute(&x, 0, &[0.0; 3], 0.5);
+    let mut text = vec![];
+    x.draw_pov(&[0.0; 3], &mut text).unwrap();
+    x.draw_pov_mesh(&[0.0; 3], &mut text).unwrap();
+    x.draw_gnuplot(&[0.0; 3], &mut text).unwrap();
+    let format = CustomFormat::new("%i %v %n %t %c").unwrap();
+    x.output_custom(&format, 0, &[0.0; 3], 0.5).unwrap();
+    let y = VoronoiCell::from(&x);
+    assert!(y.number_of_vertices() == 0);
+    x.translate(&[1.0; 3]);
+    // Cutting an empty cell reports that it was deleted.
+    assert!(!x.nplane(&[0.5, 0.0, 0.0], 1).unwrap());
+    assert!(!x.cut_halfspace(&[1.0, 0.0, 0.0], 0.5, Some(-1)).unwrap());
+    assert!(x.number_of_vertices() == 0);
+}
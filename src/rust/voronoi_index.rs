@@ -0,0 +1,105 @@
+use std::fmt;
+
+/// The Voronoi index of a cell, ⟨n3,n4,n5,n6,…⟩, where `n_i` is the number of
+/// faces of the cell which have `i` edges.
+///
+/// The Voronoi index is commonly used for classifying the local structure
+/// around a particle. For example, an icosahedral arrangement of neighbors
+/// has the index ⟨0,0,12,0⟩, and a body-centered cubic arrangement has the
+/// index ⟨0,6,0,8⟩.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct VoronoiIndex {
+    /// The number of faces with 3, 4, 5, ... edges, without trailing zeros.
+    counts: Vec<i32>,
+}
+
+impl VoronoiIndex {
+    /// Makes a Voronoi index from the numbers of faces with 3, 4, 5, ...
+    /// edges.
+    pub fn new(counts: &[i32]) -> Self {
+        let len = counts.iter().rposition(|&n| n != 0).map_or(0, |i| i + 1);
+        Self {
+            counts: counts[..len].to_vec(),
+        }
+    }
+
+    /// Makes a Voronoi index from a frequency table of face orders, such as
+    /// the one returned by the method `VoronoiCellBase::face_freq_table`. The
+    /// entry at index `n` of the table is the number of faces with `n` edges.
+    pub fn from_face_freq_table(table: &[i32]) -> Self {
+        Self::new(table.get(3..).unwrap_or(&[]))
+    }
+
+    /// Returns the number of faces which have the given number of edges.
+    pub fn count(&self, edges: usize) -> i32 {
+        edges
+            .checked_sub(3)
+            .and_then(|i| self.counts.get(i))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the numbers of faces with 3, 4, 5, ... edges, up to the
+    /// largest face of the cell.
+    pub fn counts(&self) -> &[i32] {
+        &self.counts
+    }
+
+    /// Returns the total number of faces.
+    pub fn number_of_faces(&self) -> i32 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Formats the Voronoi index as ⟨n3,n4,n5,n6⟩. Faces with more than six edges
+/// are appended to the end, as ⟨n3,n4,n5,n6,n7,…⟩.
+impl fmt::Display for VoronoiIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⟨")?;
+        for edges in 3..(self.counts.len() + 3).max(7) {
+            if edges > 3 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", self.count(edges))?;
+        }
+        write!(f, "⟩")
+    }
+}
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    use crate::{VoronoiCell, VoronoiCellBase};
+    let cube = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    assert!(cube.vertex_orders() == vec![3; 8]);
+    assert!(cube.face_orders() == vec![4; 6]);
+    assert!(cube.face_freq_table() == vec![0, 0, 0, 0, 6]);
+    let index = cube.voronoi_index();
+    assert!(index.counts() == [0, 6]);
+    assert!(index.count(4) == 6);
+    assert!(index.count(2) == 0);
+    assert!(index.count(9) == 0);
+    assert!(index.number_of_faces() == 6);
+    assert!(index.to_string() == "⟨0,6,0,0⟩");
+
+    // Cut the corners off of an octahedron to make a truncated octahedron,
+    // which is the Voronoi cell of the body-centered cubic lattice.
+    let mut x = VoronoiCell::init_octahedron(3.0);
+    for i in 0..3 {
+        let mut normal = [0.0; 3];
+        normal[i] = 1.0;
+        assert!(x.cut_plane(&normal, 4.0, 0).unwrap());
+        normal[i] = -1.0;
+        assert!(x.cut_plane(&normal, 4.0, 0).unwrap());
+    }
+    assert!(x.voronoi_index() == VoronoiIndex::new(&[0, 6, 0, 8, 0]));
+    assert!(x.voronoi_index().to_string() == "⟨0,6,0,8⟩");
+    assert!(x.vertex_orders().iter().all(|&n| n == 3));
+    let mut orders = x.face_orders();
+    orders.sort();
+    assert!(orders == [4, 4, 4, 4, 4, 4, 6, 6, 6, 6, 6, 6, 6, 6]);
+
+    let heptagon = VoronoiIndex::from_face_freq_table(&[0, 0, 0, 1, 2, 0, 0, 3]);
+    assert!(heptagon.to_string() == "⟨1,2,0,0,3⟩");
+    assert!(VoronoiIndex::default().to_string() == "⟨0,0,0,0⟩");
+}
@@ -0,0 +1,425 @@
+use crate::rust::error::Error;
+use crate::rust::voronoi_cell::VoronoiCell;
+use crate::rust::voronoi_cell_base::VoronoiCellBase;
+use crate::rust::voronoi_cell_neighbor::VoronoiCellNeighbor;
+use cpp::cpp;
+use std::os::raw::c_char;
+
+cpp! {{
+    #include "voro++.hh"
+    using namespace voro;
+
+    // A C++ wall which forwards all of its methods to a wall which is
+    // implemented in Rust.
+    class voroxx_rust_wall : public wall {
+        public:
+            voroxx_rust_wall(void *rust_wall_) : rust_wall(rust_wall_) {}
+            bool point_inside(double x, double y, double z) {
+                void *w = rust_wall;
+                double xyz[3] = {x, y, z};
+                double *p = xyz;
+                return rust!(voroxx_rust_wall_point_inside [
+                        w: *const Box<dyn Wall> as "void*", p: &[f64; 3] as "double*"]
+                        -> bool as "bool" {
+                    (*w).point_inside(p)
+                });
+            }
+            bool cut_cell(voronoicell &c, double x, double y, double z) {
+                void *w = rust_wall;
+                voronoicell *cell = &c;
+                double xyz[3] = {x, y, z};
+                double *p = xyz;
+                int status = 0;
+                int *s = &status;
+                char *msg = nullptr;
+                char **m = &msg;
+                bool retval = rust!(voroxx_rust_wall_cut_cell [
+                        w: *const Box<dyn Wall> as "void*", cell: *mut std::ffi::c_void as "voronoicell*",
+                        p: &[f64; 3] as "double*", s: &mut i32 as "int*",
+                        m: &mut *mut c_char as "char**"] -> bool as "bool" {
+                    let mut cell = std::mem::ManuallyDrop::new(VoronoiCell(cell));
+                    (*w).cut_cell(&mut *cell, p).unwrap_or_else(|error| {
+                        (*s, *m) = error.into_raw();
+                        false
+                    })
+                });
+                if(msg != nullptr) voroxx_throw_rust_error(status, msg);
+                return retval;
+            }
+            bool cut_cell(voronoicell_neighbor &c, double x, double y, double z) {
+                void *w = rust_wall;
+                voronoicell_neighbor *cell = &c;
+                double xyz[3] = {x, y, z};
+                double *p = xyz;
+                int status = 0;
+                int *s = &status;
+                char *msg = nullptr;
+                char **m = &msg;
+                bool retval = rust!(voroxx_rust_wall_cut_cell_neighbor [
+                        w: *const Box<dyn Wall> as "void*",
+                        cell: *mut std::ffi::c_void as "voronoicell_neighbor*",
+                        p: &[f64; 3] as "double*", s: &mut i32 as "int*",
+                        m: &mut *mut c_char as "char**"] -> bool as "bool" {
+                    let mut cell = std::mem::ManuallyDrop::new(VoronoiCellNeighbor(cell));
+                    (*w).cut_cell(&mut *cell, p).unwrap_or_else(|error| {
+                        (*s, *m) = error.into_raw();
+                        false
+                    })
+                });
+                if(msg != nullptr) voroxx_throw_rust_error(status, msg);
+                return retval;
+            }
+        private:
+            void *rust_wall;
+    };
+}}
+
+/// A wall restricts the Voronoi cells of a container to a non-rectangular
+/// domain. After a wall is added to a container, every Voronoi cell which the
+/// container computes is cut by the wall. Faces which are made by a wall have
+/// the wall's ID as their neighbor, which should be a negative number that is
+/// different from the container's own boundary IDs (-1 to -6).
+///
+/// Voro++ provides the wall types `WallPlane`, `WallSphere`, `WallCylinder`,
+/// and `WallCone`. Other shapes of walls can be made by implementing this
+/// trait, and the containers will call back into the Rust implementation
+/// while they compute the Voronoi cells. Panicking inside of these methods
+/// aborts the program, because the panic can not unwind through the C++ code.
+/// Walls must be thread-safe, because the parallel computation of a container
+/// calls them from many threads at once, and they must not contain any
+/// non-static references.
+pub trait Wall: Send + Sync + private::WallFFI {
+    /// Tests to see whether a point is inside the wall object.
+    fn point_inside(&self, xyz: &[f64; 3]) -> bool;
+
+    /// Cuts a Voronoi cell by the wall object. The wall should cut the cell
+    /// with the method `VoronoiCellBase::cut_plane`, using its ID number for
+    /// the plane ID.
+    ///
+    /// __Parameters:__
+    /// * `cell` The Voronoi cell to be cut. Its vertices are in the local
+    ///   coordinate system, relative to the particle position.
+    /// * `xyz` The position of the particle.
+    ///
+    /// __Returns:__
+    ///     False if the wall cut deleted the cell entirely, true otherwise.
+    ///     Errors are passed through the container's cell computation, and
+    ///     are returned by its iterator.
+    fn cut_cell(&self, cell: &mut dyn VoronoiCellBase, xyz: &[f64; 3]) -> Result<bool, Error>;
+}
+
+mod private {
+    use super::{WallCone, WallCylinder, WallPlane, WallSphere};
+    use std::any::Any;
+
+    /// Private trait. Allows the containers to access the pointer to the
+    /// underlying C++ structure of the built-in wall types. It is implemented
+    /// for every type, so that it can not be overridden by walls which are
+    /// implemented outside of this crate.
+    pub trait WallFFI {
+        /// Returns the pointer to the C++ wall, or null for walls which are
+        /// implemented in Rust.
+        fn ptr(&self) -> *mut std::ffi::c_void;
+    }
+
+    impl<T: Any> WallFFI for T {
+        fn ptr(&self) -> *mut std::ffi::c_void {
+            let wall = self as &dyn Any;
+            if let Some(w) = wall.downcast_ref::<WallPlane>() {
+                w.ptr
+            } else if let Some(w) = wall.downcast_ref::<WallSphere>() {
+                w.ptr
+            } else if let Some(w) = wall.downcast_ref::<WallCylinder>() {
+                w.ptr
+            } else if let Some(w) = wall.downcast_ref::<WallCone>() {
+                w.ptr
+            } else {
+                std::ptr::null_mut()
+            }
+        }
+    }
+}
+
+/// The walls which belong to a container. The C++ container only stores
+/// pointers to its walls, so the walls are kept alive here, along with the
+/// C++ trampolines which call into walls that are implemented in Rust.
+pub(crate) struct Walls {
+    // The trampolines point to the inner boxes, which must never move.
+    #[allow(clippy::vec_box)]
+    walls: Vec<Box<Box<dyn Wall>>>,
+    trampolines: Vec<*mut std::ffi::c_void>,
+}
+
+impl Walls {
+    pub(crate) fn new() -> Self {
+        Self {
+            walls: vec![],
+            trampolines: vec![],
+        }
+    }
+
+    /// Takes ownership of a wall, and returns a pointer to a C++ wall object
+    /// which is valid for as long as this structure is alive.
+    pub(crate) fn push(&mut self, wall: Box<dyn Wall>) -> *mut std::ffi::c_void {
+        let wall = Box::new(wall);
+        let mut wall_ptr = private::WallFFI::ptr(&**wall);
+        if wall_ptr.is_null() {
+            let rust_wall: *const Box<dyn Wall> = &*wall;
+            wall_ptr = cpp!(unsafe [rust_wall as "void*"]
+                    -> *mut std::ffi::c_void as "voroxx_rust_wall*" {
+                return new voroxx_rust_wall(rust_wall);
+            });
+            self.trampolines.push(wall_ptr);
+        }
+        self.walls.push(wall);
+        wall_ptr
+    }
+}
+
+impl Drop for Walls {
+    fn drop(&mut self) {
+        for &ptr in &self.trampolines {
+            cpp!(unsafe [ptr as "voroxx_rust_wall*"] {
+                delete ptr;
+            })
+        }
+    }
+}
+
+/// A plane wall object. Points are inside of the wall if they satisfy the
+/// inequality `normal · xyz < displacement`.
+pub struct WallPlane {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl WallPlane {
+    /// Constructs a plane wall object.
+    ///
+    /// __Parameters:__
+    /// * `normal` A normal vector to the plane, pointing out of the wall.
+    /// * `displacement` A displacement along the normal vector. If the normal
+    ///   vector has unit length then this is the distance of the plane from
+    ///   the origin.
+    /// * `id` An ID number to associate with the wall for neighbor tracking.
+    pub fn new(normal: &[f64; 3], displacement: f64, id: i32) -> Self {
+        Self {
+            ptr: cpp!(unsafe [normal as "double*", displacement as "double", id as "int"]
+                    -> *mut std::ffi::c_void as "wall_plane*" {
+                return new wall_plane(normal[0], normal[1], normal[2], displacement, id);
+            }),
+        }
+    }
+}
+
+/// A spherical wall object, which keeps the cells inside of the sphere.
+///
+/// The wall is approximated by a single plane cut, applied at the point on the
+/// sphere which is closest to the particle. This works well for particle
+/// arrangements which are packed against the wall, but loses accuracy for
+/// sparse particle distributions.
+pub struct WallSphere {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl WallSphere {
+    /// Constructs a spherical wall object.
+    ///
+    /// __Parameters:__
+    /// * `center` A position vector for the sphere's center.
+    /// * `radius` The radius of the sphere.
+    /// * `id` An ID number to associate with the wall for neighbor tracking.
+    pub fn new(center: &[f64; 3], radius: f64, id: i32) -> Self {
+        Self {
+            ptr: cpp!(unsafe [center as "double*", radius as "double", id as "int"]
+                    -> *mut std::ffi::c_void as "wall_sphere*" {
+                return new wall_sphere(center[0], center[1], center[2], radius, id);
+            }),
+        }
+    }
+}
+
+/// An open cylindrical wall object, of infinite length, which keeps the cells
+/// inside of the cylinder.
+///
+/// The wall is approximated by a single plane cut, in the same way as the
+/// `WallSphere`.
+pub struct WallCylinder {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl WallCylinder {
+    /// Constructs a cylindrical wall object.
+    ///
+    /// __Parameters:__
+    /// * `point` A point on the axis of the cylinder.
+    /// * `axis` A vector pointing along the direction of the cylinder.
+    /// * `radius` The radius of the cylinder.
+    /// * `id` An ID number to associate with the wall for neighbor tracking.
+    pub fn new(point: &[f64; 3], axis: &[f64; 3], radius: f64, id: i32) -> Self {
+        Self {
+            ptr: cpp!(unsafe [point as "double*", axis as "double*", radius as "double",
+                    id as "int"] -> *mut std::ffi::c_void as "wall_cylinder*" {
+                return new wall_cylinder(point[0], point[1], point[2],
+                    axis[0], axis[1], axis[2], radius, id);
+            }),
+        }
+    }
+}
+
+/// A conical wall object, of infinite length, which keeps the cells inside of
+/// the cone.
+///
+/// The wall is approximated by a single plane cut, in the same way as the
+/// `WallSphere`.
+pub struct WallCone {
+    ptr: *mut std::ffi::c_void,
+}
+
+impl WallCone {
+    /// Constructs a conical wall object.
+    ///
+    /// __Parameters:__
+    /// * `apex` The apex of the cone.
+    /// * `axis` A vector pointing along the axis of the cone, away from the
+    ///   apex.
+    /// * `angle` The angle of the cone in radians, measured from the axis.
+    /// * `id` An ID number to associate with the wall for neighbor tracking.
+    pub fn new(apex: &[f64; 3], axis: &[f64; 3], angle: f64, id: i32) -> Self {
+        Self {
+            ptr: cpp!(unsafe [apex as "double*", axis as "double*", angle as "double",
+                    id as "int"] -> *mut std::ffi::c_void as "wall_cone*" {
+                return new wall_cone(apex[0], apex[1], apex[2],
+                    axis[0], axis[1], axis[2], angle, id);
+            }),
+        }
+    }
+}
+
+/// Implements the common traits for the built-in wall types.
+macro_rules! impl_builtin_wall {
+    ($name:ident) => {
+        impl Wall for $name {
+            fn point_inside(&self, xyz: &[f64; 3]) -> bool {
+                let ptr = self.ptr;
+                cpp!(unsafe [ptr as "wall*", xyz as "double*"] -> bool as "bool" {
+                    return ptr->point_inside(xyz[0], xyz[1], xyz[2]);
+                })
+            }
+
+            fn cut_cell(
+                &self,
+                cell: &mut dyn VoronoiCellBase,
+                xyz: &[f64; 3],
+            ) -> Result<bool, Error> {
+                // The C++ wall lives for as long as this object.
+                unsafe { cell.cut_by_wall(self.ptr, xyz) }
+            }
+        }
+
+        // The C++ wall objects are never modified after they are made.
+        unsafe impl Send for $name {}
+        unsafe impl Sync for $name {}
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                let ptr = self.ptr;
+                cpp!(unsafe [ptr as "wall*"] {
+                    delete ptr;
+                })
+            }
+        }
+    };
+}
+
+impl_builtin_wall!(WallPlane);
+impl_builtin_wall!(WallSphere);
+impl_builtin_wall!(WallCylinder);
+impl_builtin_wall!(WallCone);
+
+/// Call every public API entry point. Check for sane results & no program crashes.
+#[test]
+fn ffi_sanity() {
+    let plane = WallPlane::new(&[1.0, 0.0, 0.0], 0.5, -7);
+    assert!(plane.point_inside(&[0.4, 9.0, 9.0]));
+    assert!(!plane.point_inside(&[0.6, 0.0, 0.0]));
+
+    let sphere = WallSphere::new(&[1.0, 1.0, 1.0], 0.5, -8);
+    assert!(sphere.point_inside(&[1.2, 1.2, 1.2]));
+    assert!(!sphere.point_inside(&[0.0, 0.0, 0.0]));
+
+    let cylinder = WallCylinder::new(&[0.0, 0.0, 0.0], &[0.0, 0.0, 2.0], 1.0, -9);
+    assert!(cylinder.point_inside(&[0.5, 0.5, 100.0]));
+    assert!(!cylinder.point_inside(&[1.0, 1.0, 0.0]));
+
+    let cone = WallCone::new(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0], 0.25, -10);
+    assert!(cone.point_inside(&[0.1, 0.0, 1.0]));
+    assert!(!cone.point_inside(&[0.5, 0.0, 1.0]));
+}
+
+/// Check that a wall which is implemented in Rust cuts the cells in the same
+/// way as the equivalent built-in wall.
+#[test]
+fn rust_wall() {
+    use crate::{Container, ContainerBase, VoronoiCell};
+    struct HalfSpace {
+        normal: [f64; 3],
+        displacement: f64,
+        id: i32,
+    }
+    impl Wall for HalfSpace {
+        fn point_inside(&self, xyz: &[f64; 3]) -> bool {
+            let d: f64 = (0..3).map(|i| xyz[i] * self.normal[i]).sum();
+            d < self.displacement
+        }
+        fn cut_cell(&self, cell: &mut dyn VoronoiCellBase, xyz: &[f64; 3]) -> Result<bool, Error> {
+            let d: f64 = (0..3).map(|i| xyz[i] * self.normal[i]).sum();
+            cell.cut_plane(&self.normal, 2.0 * (self.displacement - d), self.id)
+        }
+    }
+    let normal = [1.0, 1.0, 1.0];
+    let built_in = WallPlane::new(&normal, 1.5, -7);
+    let rust = HalfSpace {
+        normal,
+        displacement: 1.5,
+        id: -7,
+    };
+    // Only the built-in walls provide a C++ wall.
+    assert!(!private::WallFFI::ptr(&built_in).is_null());
+    assert!(private::WallFFI::ptr(&rust).is_null());
+    for xyz in [[0.2, 0.3, 0.4], [0.9, 0.9, 0.9]] {
+        assert!(built_in.point_inside(&xyz) == rust.point_inside(&xyz));
+    }
+    let mut a = VoronoiCell::init(&[-1.0; 3], &[1.0; 3]);
+    let mut b = a.clone();
+    assert!(built_in.cut_cell(&mut a, &[0.5; 3]).unwrap());
+    assert!(rust.cut_cell(&mut b, &[0.5; 3]).unwrap());
+    assert!((a.volume() - b.volume()).abs() < 1e-12);
+
+    fn volumes(wall: impl Wall + 'static) -> Vec<(i32, f64, Vec<i32>)> {
+        let mut con = Container::new(&[0.0; 3], &[1.0; 3], &[2, 2, 2], &[false; 3], 8).unwrap();
+        con.add_wall(wall).unwrap();
+        for i in 0..8 {
+            let xyz = [0.1 * i as f64, 0.37 * i as f64 % 1.0, 0.61 * i as f64 % 1.0];
+            con.put(i, &xyz).unwrap();
+        }
+        assert!(con.point_inside_walls(&[0.1, 0.1, 0.1]));
+        assert!(!con.point_inside_walls(&[0.9, 0.9, 0.9]));
+        let mut cells: Vec<_> = con
+            .cells_neighbor()
+            .map(Result::unwrap)
+            .map(|(id, _, _, cell)| (id, cell.volume(), cell.neighbors()))
+            .collect();
+        cells.sort_by_key(|(id, _, _)| *id);
+        cells
+    }
+    let expected = volumes(built_in);
+    let actual = volumes(rust);
+    assert!(expected.len() == actual.len());
+    assert!(expected
+        .iter()
+        .any(|(_, _, neighbors)| neighbors.contains(&-7)));
+    for (e, a) in expected.iter().zip(&actual) {
+        assert!(e.0 == a.0 && e.2 == a.2);
+        assert!((e.1 - a.1).abs() < 1e-12);
+    }
+}